@@ -23,7 +23,14 @@ async fn main() -> Result<(), Box<dyn error::Error>> {
             )])),
             max_retries: 3,
             timeout: 10,
+            target: Default::default(),
         })],
+        rules: Vec::new(),
+        store: Default::default(),
+        dead_letter_dir: None,
+        health_check_interval_ms: 30000,
+        subscriber_capacity: 1024,
+        overflow: Default::default(),
         http: Default::default(),
     };
     config.http.port = 8080;
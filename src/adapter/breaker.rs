@@ -0,0 +1,173 @@
+use crate::ChannelAdapter;
+use crate::CircuitBreakerConfig;
+use crate::Error;
+use crate::Event;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CircuitState {
+    /// events are sent to the target
+    Closed,
+    /// the target failed repeatedly, events are rejected without a send attempt
+    Open,
+    /// the open timeout elapsed, a single probe is sent to find out whether the target recovered
+    HalfOpen,
+}
+
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Instant,
+    probing: bool,
+}
+
+/// A circuit breaker around a [`ChannelAdapter`].
+///
+/// The circuit opens after `failure_threshold` consecutive sends failing with a retryable error,
+/// from then on sends fail right away with [`Error::CircuitOpen`]. The event bus never dead-letters
+/// these events, it keeps them pending and sends them again once the circuit lets them through.
+/// Once `open_timeout_ms` has elapsed a single probe is let through, closing the circuit again if it
+/// succeeds.
+pub struct CircuitBreaker {
+    inner: Arc<dyn ChannelAdapter>,
+    failure_threshold: u32,
+    open_timeout: Duration,
+    circuit: Mutex<Circuit>,
+}
+
+impl CircuitBreaker {
+    /// Wraps `inner` in a circuit breaker.
+    pub fn new(inner: Arc<dyn ChannelAdapter>, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            failure_threshold: config.failure_threshold,
+            open_timeout: Duration::from_millis(config.open_timeout_ms),
+            circuit: Mutex::new(Circuit {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: Instant::now(),
+                probing: false,
+            }),
+        }
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let circuit = self.lock();
+        match circuit.state {
+            CircuitState::Open if circuit.opened_at.elapsed() >= self.open_timeout => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// Returns the number of consecutive failed sends.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.lock().consecutive_failures
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Circuit> {
+        self.circuit.lock().expect("circuit breaker poisoned")
+    }
+
+    /// Decides whether a send may go ahead, returns whether it is the half-open probe.
+    fn admit(&self) -> Result<bool, Error> {
+        let mut circuit = self.lock();
+        if circuit.state == CircuitState::Open && circuit.opened_at.elapsed() >= self.open_timeout {
+            circuit.state = CircuitState::HalfOpen;
+        }
+        match circuit.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::HalfOpen if !circuit.probing => {
+                circuit.probing = true;
+                Ok(true)
+            }
+            _ => Err(Error::CircuitOpen(self.inner.id())),
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut circuit = self.lock();
+        circuit.probing = false;
+        if success {
+            if circuit.state != CircuitState::Closed {
+                tracing::info!("Circuit of {} closed", self.inner.id());
+            }
+            circuit.state = CircuitState::Closed;
+            circuit.consecutive_failures = 0;
+            return;
+        }
+        circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+        if circuit.state == CircuitState::HalfOpen
+            || circuit.consecutive_failures >= self.failure_threshold
+        {
+            if circuit.state != CircuitState::Open {
+                tracing::warn!(
+                    "Circuit of {} opened after {} consecutive failures",
+                    self.inner.id(),
+                    circuit.consecutive_failures
+                );
+            }
+            circuit.state = CircuitState::Open;
+            circuit.opened_at = Instant::now();
+        }
+    }
+
+    /// Runs `send` if the circuit admits it and records its outcome.
+    async fn guard(&self, send: impl Future<Output = Result<(), Error>>) -> Result<(), Error> {
+        let probe = self.admit()?;
+        let mut guard = ProbeGuard {
+            breaker: self,
+            armed: probe,
+        };
+        let result = send.await;
+        guard.armed = false;
+        // a permanent error such as a rejected payload shows the target itself is reachable
+        self.record(result.as_ref().map_or_else(|e| !e.is_retryable(), |_| true));
+        result
+    }
+}
+
+/// Releases the half-open probe if its send is dropped before completing.
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.breaker.lock().probing = false;
+        }
+    }
+}
+
+#[async_trait]
+impl ChannelAdapter for CircuitBreaker {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    async fn is_active(&self) -> Result<bool, Error> {
+        self.inner.is_active().await
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        self.guard(self.inner.send(event)).await
+    }
+
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        self.guard(self.inner.send_batch(events)).await
+    }
+}
@@ -0,0 +1,102 @@
+use crate::ChannelAdapter;
+use crate::Error;
+use crate::Event;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// A [`ChannelAdapter`] gated by the periodic health check of its target.
+///
+/// While the last [`ChannelAdapter::is_active`] check failed, sends fail right away with
+/// [`Error::TargetInactive`]. The event bus never dead-letters these events, it keeps them pending
+/// and sends them again once a check passes. The target is assumed to be active until the first
+/// check, one interval after the system starts.
+pub struct HealthCheck {
+    inner: Arc<dyn ChannelAdapter>,
+    active: AtomicBool,
+    last_checked: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl HealthCheck {
+    /// Wraps `inner` in a health check.
+    pub fn new(inner: Arc<dyn ChannelAdapter>) -> Self {
+        Self {
+            inner,
+            active: AtomicBool::new(true),
+            last_checked: Mutex::new(None),
+        }
+    }
+
+    /// Returns whether the last health check passed.
+    pub fn active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Returns when the target was last checked, `None` before the first check.
+    pub fn last_checked(&self) -> Option<DateTime<Utc>> {
+        *self.last_checked.lock().expect("health check poisoned")
+    }
+
+    /// Checks the target and records the outcome, returns whether it is active.
+    pub async fn check(&self) -> bool {
+        let active = match self.inner.is_active().await {
+            Ok(active) => active,
+            Err(e) => {
+                tracing::debug!("Health check of {} failed: {}", self.inner.id(), e);
+                false
+            }
+        };
+        if self.active.swap(active, Ordering::SeqCst) != active {
+            if active {
+                tracing::info!("Target {} is active again", self.inner.id());
+            } else {
+                tracing::warn!("Target {} failed its health check", self.inner.id());
+            }
+        }
+        *self.last_checked.lock().expect("health check poisoned") = Some(Utc::now());
+        active
+    }
+
+    /// Checks the target every `interval` until `shutdown` is cancelled.
+    pub(crate) async fn run(self: Arc<Self>, interval: Duration, shutdown: CancellationToken) {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.cancelled() => break,
+            }
+            self.check().await;
+        }
+    }
+}
+
+#[async_trait]
+impl ChannelAdapter for HealthCheck {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    async fn is_active(&self) -> Result<bool, Error> {
+        self.inner.is_active().await
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        if !self.active() {
+            return Err(Error::TargetInactive(self.inner.id()));
+        }
+        self.inner.send(event).await
+    }
+
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        if !self.active() {
+            return Err(Error::TargetInactive(self.inner.id()));
+        }
+        self.inner.send_batch(events).await
+    }
+}
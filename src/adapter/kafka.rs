@@ -2,19 +2,22 @@ use crate::ChannelAdapter;
 use crate::Error;
 use crate::Event;
 use crate::KafkaConfig;
+use crate::NameFormat;
+use crate::PayloadFormat;
 use async_trait::async_trait;
 use rdkafka::error::KafkaError;
-use rdkafka::producer::{FutureProducer, FutureRecord};
-use rdkafka::types::RDKafkaErrorCode;
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
 use rdkafka::util::Timeout;
 use std::time::Duration;
-use tokio::time::sleep;
 
 /// Kafka adapter for sending events to a Kafka topic.
 pub struct KafkaAdapter {
     producer: FutureProducer,
+    id: Option<String>,
+    format: PayloadFormat,
+    name_format: NameFormat,
     topic: String,
-    max_retries: u32,
+    timeout: Duration,
 }
 
 impl KafkaAdapter {
@@ -28,39 +31,28 @@ impl KafkaAdapter {
 
         Ok(Self {
             producer,
+            id: config.target.id.clone(),
+            format: config.target.format,
+            name_format: config.target.name_format,
             topic: config.topic.clone(),
-            max_retries: config.max_retries,
+            timeout: Duration::from_millis(config.timeout),
         })
     }
-    /// Sends an event to the Kafka topic with retry logic.
-    async fn send_with_retry(&self, event: &Event) -> Result<(), Error> {
+    /// Sends an event to the Kafka topic, keyed by the event id.
+    async fn produce(&self, event: &Event) -> Result<(), Error> {
         let event_id = event.id.to_string();
-        let payload = serde_json::to_string(&event)?;
-
-        for attempt in 0..self.max_retries {
-            let record = FutureRecord::to(&self.topic)
-                .key(&event_id)
-                .payload(&payload);
-
-            match self.producer.send(record, Timeout::Never).await {
-                Ok(_) => return Ok(()),
-                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
-                    tracing::warn!(
-                        "Kafka attempt {} failed: Queue full. Retrying...",
-                        attempt + 1
-                    );
-                    sleep(Duration::from_secs(2u64.pow(attempt))).await;
-                }
-                Err((e, _)) => {
-                    tracing::error!("Kafka send error: {}", e);
-                    return Err(Error::Kafka(Box::new(e)));
-                }
+        let payload = event.to_payload(self.format, self.name_format)?;
+        let record = FutureRecord::to(&self.topic)
+            .key(&event_id)
+            .payload(&payload);
+
+        match self.producer.send(record, Timeout::Never).await {
+            Ok(_) => Ok(()),
+            Err((e, _)) => {
+                tracing::error!("Kafka send error: {}", e);
+                Err(Error::Kafka(Box::new(e)))
             }
         }
-
-        Err(Error::Custom(
-            "Exceeded maximum retry attempts for Kafka message".to_string(),
-        ))
     }
 }
 
@@ -70,7 +62,162 @@ impl ChannelAdapter for KafkaAdapter {
         "kafka".to_string()
     }
 
+    fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Fetches the metadata of the topic from the brokers.
+    async fn is_active(&self) -> Result<bool, Error> {
+        let producer = self.producer.clone();
+        let topic = self.topic.clone();
+        let timeout = self.timeout;
+        // fetching metadata blocks until the brokers answer or the timeout elapses
+        let metadata = tokio::task::spawn_blocking(move || {
+            producer
+                .client()
+                .fetch_metadata(Some(topic.as_str()), timeout)
+        })
+        .await?;
+        Ok(metadata.is_ok())
+    }
+
     async fn send(&self, event: &Event) -> Result<(), Error> {
-        self.send_with_retry(event).await
+        self.produce(event).await
+    }
+
+    /// Enqueues every event before waiting for the deliveries, so the producer sends them together.
+    ///
+    /// Every enqueued delivery is awaited even once one failed, events that failed or could not be
+    /// enqueued are reported through [`Error::PartialBatch`].
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        let event_ids: Vec<String> = events.iter().map(|event| event.id.to_string()).collect();
+        let payloads = events
+            .iter()
+            .map(|event| event.to_payload(self.format, self.name_format))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut deliveries = Vec::with_capacity(events.len());
+        let mut failed = Vec::new();
+        for (event_id, payload) in event_ids.iter().zip(&payloads) {
+            let record = FutureRecord::to(&self.topic).key(event_id).payload(payload);
+            match self.producer.send_result(record) {
+                Ok(delivery) => deliveries.push(delivery),
+                Err((e, _)) => {
+                    // the producer queue is full, the remaining events are sent again later
+                    failed.extend((deliveries.len()..events.len()).map(|i| (i, e.clone())));
+                    break;
+                }
+            }
+        }
+        for (i, delivery) in deliveries.into_iter().enumerate() {
+            let e = match delivery.await {
+                Ok(Ok(_)) => continue,
+                Ok(Err((e, _))) => e,
+                Err(_) => KafkaError::Canceled,
+            };
+            tracing::error!("Kafka send error: {}", e);
+            failed.push((i, e));
+        }
+        batch_result(events.len(), failed)
+    }
+}
+
+/// Returns the outcome of a batch of `len` events from the errors of the failed events, by index.
+fn batch_result(len: usize, mut failed: Vec<(usize, KafkaError)>) -> Result<(), Error> {
+    failed.sort_unstable_by_key(|(i, _)| *i);
+    let (indices, errors): (Vec<_>, Vec<_>) = failed.into_iter().unzip();
+    let Some(e) = errors.into_iter().next() else {
+        return Ok(());
+    };
+    let error = Error::Kafka(Box::new(e));
+    if indices.len() == len {
+        Err(error)
+    } else {
+        Err(Error::PartialBatch(indices, Box::new(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+    use crate::adapter::test_event;
+    use rdkafka::types::RDKafkaErrorCode;
+
+    fn kafka_config(brokers: &str, topic: &str) -> KafkaConfig {
+        serde_json::from_value(serde_json::json!({
+            "brokers": brokers,
+            "topic": topic,
+            "max_retries": 1,
+            "timeout": 500,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_batch_result() {
+        let timed_out = || KafkaError::MessageProduction(RDKafkaErrorCode::MessageTimedOut);
+        assert!(batch_result(3, Vec::new()).is_ok());
+
+        // a full producer queue fails the events that were not enqueued, after a failed delivery
+        let failed = vec![(2, KafkaError::Canceled), (0, timed_out())];
+        match batch_result(3, failed) {
+            Err(Error::PartialBatch(indices, e)) => {
+                assert_eq!(indices, vec![0, 2]);
+                assert!(matches!(*e, Error::Kafka(e) if *e == timed_out()));
+            }
+            other => panic!("unexpected outcome {other:?}"),
+        }
+
+        let failed = vec![(1, timed_out()), (0, timed_out())];
+        assert!(matches!(batch_result(2, failed), Err(Error::Kafka(_))));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_to_unreachable_broker() {
+        let adapter = KafkaAdapter::new(&kafka_config("127.0.0.1:1", "events")).unwrap();
+        let events = [
+            test_event(Name::ObjectCreatedPut, "photos", "a.jpg"),
+            test_event(Name::ObjectCreatedPut, "photos", "b.jpg"),
+        ];
+
+        // every delivery times out, so the whole batch fails rather than a part of it
+        let result = adapter.send_batch(&events).await;
+        assert!(matches!(result, Err(Error::Kafka(_))), "{result:?}");
+        assert!(!adapter.is_active().await.unwrap());
+    }
+
+    /// Sends a batch to the brokers in `KAFKA_BROKERS` and reads it back.
+    #[tokio::test]
+    #[ignore = "needs a Kafka broker at KAFKA_BROKERS"]
+    async fn test_kafka_round_trip() {
+        use rdkafka::Message;
+        use rdkafka::consumer::{Consumer, StreamConsumer};
+
+        let brokers = std::env::var("KAFKA_BROKERS").unwrap_or("127.0.0.1:9092".to_string());
+        let topic = format!("events-{}", uuid::Uuid::new_v4());
+        let mut config = kafka_config(&brokers, &topic);
+        config.timeout = 10000;
+        let adapter = KafkaAdapter::new(&config).unwrap();
+        let events = [
+            test_event(Name::ObjectCreatedPut, "photos", "a.jpg"),
+            test_event(Name::ObjectRemovedDelete, "photos", "a.jpg"),
+        ];
+        adapter.send_batch(&events).await.unwrap();
+
+        let consumer: StreamConsumer = rdkafka::config::ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .set("group.id", &topic)
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .unwrap();
+        consumer.subscribe(&[&topic]).unwrap();
+        for event in &events {
+            let message = consumer.recv().await.unwrap();
+            let key = event.id.to_string();
+            assert_eq!(message.key(), Some(key.as_bytes()));
+            let received: Event = serde_json::from_slice(message.payload().unwrap()).unwrap();
+            assert_eq!(received.event_name, event.event_name);
+        }
     }
 }
@@ -2,22 +2,65 @@ use crate::AdapterConfig;
 use crate::Error;
 use crate::Event;
 use async_trait::async_trait;
+use std::collections::HashSet;
 use std::sync::Arc;
 
+#[cfg(feature = "amqp")]
+pub(crate) mod amqp;
+pub(crate) mod breaker;
+pub(crate) mod health;
 #[cfg(feature = "kafka")]
 pub(crate) mod kafka;
 #[cfg(feature = "mqtt")]
 pub(crate) mod mqtt;
+#[cfg(feature = "mysql")]
+pub(crate) mod mysql;
+#[cfg(feature = "nats")]
+pub(crate) mod nats;
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
+#[cfg(feature = "redis")]
+pub(crate) mod redis;
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub(crate) mod table;
 #[cfg(feature = "webhook")]
 pub(crate) mod webhook;
 
 /// The `ChannelAdapter` trait defines the interface for all channel adapters.
 #[async_trait]
 pub trait ChannelAdapter: Send + Sync + 'static {
-    /// Sends an event to the channel.
+    /// Returns the name of the adapter kind, e.g. `webhook`.
     fn name(&self) -> String;
+    /// Returns the unique identifier events are routed to this adapter by.
+    ///
+    /// Defaults to the adapter name, so a single adapter of each kind needs no explicit id.
+    fn id(&self) -> String {
+        self.name()
+    }
+    /// Checks whether the target is reachable, run periodically by the notification system.
+    ///
+    /// Defaults to `Ok(true)` for adapters that cannot tell.
+    async fn is_active(&self) -> Result<bool, Error> {
+        Ok(true)
+    }
     /// Sends an event to the channel.
     async fn send(&self, event: &Event) -> Result<(), Error>;
+    /// Sends several events to the channel, used by the event bus for targets with batching enabled.
+    ///
+    /// When only some of the events were delivered, [`Error::PartialBatch`] tells the event bus
+    /// which ones to send again. Defaults to sending the events one by one, stopping at the first
+    /// failure.
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        for (i, event) in events.iter().enumerate() {
+            if let Err(e) = self.send(event).await {
+                return Err(match i {
+                    0 => e,
+                    i => Error::PartialBatch((i..events.len()).collect(), Box::new(e)),
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Creates channel adapters based on the provided configuration.
@@ -25,8 +68,19 @@ pub fn create_adapters(
     configs: &[AdapterConfig],
 ) -> Result<Vec<Arc<dyn ChannelAdapter>>, Box<Error>> {
     let mut adapters: Vec<Arc<dyn ChannelAdapter>> = Vec::new();
+    let mut ids = HashSet::new();
 
     for config in configs {
+        config
+            .validate_id()
+            .and_then(|()| config.target().validate())
+            .map_err(|e| Box::new(Error::ConfigError(e)))?;
+        if !ids.insert(config.id()) {
+            return Err(Box::new(Error::ConfigError(format!(
+                "Duplicate adapter id {}, give every adapter entry a unique id",
+                config.id()
+            ))));
+        }
         match config {
             #[cfg(feature = "webhook")]
             AdapterConfig::Webhook(webhook_config) => {
@@ -43,18 +97,99 @@ pub fn create_adapters(
             }
             #[cfg(feature = "mqtt")]
             AdapterConfig::Mqtt(mqtt_config) => {
-                let (mqtt, mut event_loop) = mqtt::MqttAdapter::new(mqtt_config);
-                tokio::spawn(async move { while event_loop.poll().await.is_ok() {} });
+                let (mqtt, event_loop) = mqtt::MqttAdapter::new(mqtt_config);
+                tokio::spawn(mqtt::poll(event_loop, mqtt.connection()));
                 adapters.push(Arc::new(mqtt));
             }
+            #[cfg(feature = "nats")]
+            AdapterConfig::Nats(nats_config) => {
+                nats_config
+                    .validate()
+                    .map_err(|e| Box::new(Error::ConfigError(e)))?;
+                adapters.push(Arc::new(nats::NatsAdapter::new(nats_config.clone())));
+            }
+            #[cfg(feature = "redis")]
+            AdapterConfig::Redis(redis_config) => {
+                redis_config
+                    .validate()
+                    .map_err(|e| Box::new(Error::ConfigError(e)))?;
+                adapters.push(Arc::new(redis::RedisAdapter::new(redis_config)?));
+            }
+            #[cfg(feature = "amqp")]
+            AdapterConfig::Amqp(amqp_config) => {
+                amqp_config
+                    .validate()
+                    .map_err(|e| Box::new(Error::ConfigError(e)))?;
+                adapters.push(Arc::new(amqp::AmqpAdapter::new(amqp_config)));
+            }
+            #[cfg(feature = "postgres")]
+            AdapterConfig::Postgres(postgres_config) => {
+                postgres_config
+                    .validate()
+                    .map_err(|e| Box::new(Error::ConfigError(e)))?;
+                adapters.push(Arc::new(postgres::PostgresAdapter::new(postgres_config)?));
+            }
+            #[cfg(feature = "mysql")]
+            AdapterConfig::Mysql(mysql_config) => {
+                mysql_config
+                    .validate()
+                    .map_err(|e| Box::new(Error::ConfigError(e)))?;
+                adapters.push(Arc::new(mysql::MysqlAdapter::new(mysql_config)?));
+            }
             #[cfg(not(feature = "webhook"))]
             AdapterConfig::Webhook(_) => return Err(Box::new(Error::FeatureDisabled("webhook"))),
             #[cfg(not(feature = "kafka"))]
             AdapterConfig::Kafka(_) => return Err(Box::new(Error::FeatureDisabled("kafka"))),
             #[cfg(not(feature = "mqtt"))]
             AdapterConfig::Mqtt(_) => return Err(Box::new(Error::FeatureDisabled("mqtt"))),
+            #[cfg(not(feature = "nats"))]
+            AdapterConfig::Nats(_) => return Err(Box::new(Error::FeatureDisabled("nats"))),
+            #[cfg(not(feature = "redis"))]
+            AdapterConfig::Redis(_) => return Err(Box::new(Error::FeatureDisabled("redis"))),
+            #[cfg(not(feature = "amqp"))]
+            AdapterConfig::Amqp(_) => return Err(Box::new(Error::FeatureDisabled("amqp"))),
+            #[cfg(not(feature = "postgres"))]
+            AdapterConfig::Postgres(_) => {
+                return Err(Box::new(Error::FeatureDisabled("postgres")));
+            }
+            #[cfg(not(feature = "mysql"))]
+            AdapterConfig::Mysql(_) => return Err(Box::new(Error::FeatureDisabled("mysql"))),
         }
     }
 
     Ok(adapters)
 }
+
+/// Creates an event of type `name` for `key` in `bucket`, for the unit tests of the adapters.
+#[cfg(test)]
+#[allow(dead_code)]
+pub(crate) fn test_event(name: crate::Name, bucket: &str, key: &str) -> Event {
+    use crate::{Bucket, Identity, Metadata, Object, Source};
+
+    let metadata = Metadata {
+        schema_version: "1.0".to_string(),
+        configuration_id: "test-config".to_string(),
+        bucket: Bucket {
+            name: bucket.to_string(),
+            owner_identity: Identity {
+                principal_id: "owner123".to_string(),
+            },
+            arn: format!("arn:aws:s3:::{bucket}"),
+        },
+        object: Object {
+            key: key.to_string(),
+            size: Some(1024),
+            etag: None,
+            content_type: None,
+            user_metadata: None,
+            version_id: None,
+            sequencer: "1234567890".to_string(),
+        },
+    };
+    let source = Source {
+        host: "localhost".to_string(),
+        port: "80".to_string(),
+        user_agent: "curl/7.68.0".to_string(),
+    };
+    Event::create(name, metadata, source, Vec::new())
+}
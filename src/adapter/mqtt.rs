@@ -2,16 +2,25 @@ use crate::ChannelAdapter;
 use crate::Error;
 use crate::Event;
 use crate::MqttConfig;
+use crate::NameFormat;
+use crate::PayloadFormat;
 use async_trait::async_trait;
-use rumqttc::{AsyncClient, MqttOptions, QoS};
+use rumqttc::{AsyncClient, EventLoop, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use tokio::time::sleep;
+
+/// Delay before polling the event loop again after a connection error, which makes it reconnect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
 
 /// MQTT adapter for sending events to an MQTT broker.
 pub struct MqttAdapter {
     client: AsyncClient,
+    id: Option<String>,
+    format: PayloadFormat,
+    name_format: NameFormat,
     topic: String,
-    max_retries: u32,
+    connected: Arc<AtomicBool>,
 }
 
 impl MqttAdapter {
@@ -22,12 +31,38 @@ impl MqttAdapter {
         (
             Self {
                 client,
+                id: config.target.id.clone(),
+                format: config.target.format,
+                name_format: config.target.name_format,
                 topic: config.topic.clone(),
-                max_retries: config.max_retries,
+                connected: Arc::new(AtomicBool::new(false)),
             },
             event_loop,
         )
     }
+
+    /// Returns the connection state updated by [`poll`].
+    pub fn connection(&self) -> Arc<AtomicBool> {
+        self.connected.clone()
+    }
+}
+
+/// Drives the event loop of the client, recording whether it is connected to the broker.
+pub async fn poll(mut event_loop: EventLoop, connected: Arc<AtomicBool>) {
+    loop {
+        match event_loop.poll().await {
+            Ok(rumqttc::Event::Incoming(Packet::ConnAck(_))) => {
+                connected.store(true, Ordering::SeqCst);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if connected.swap(false, Ordering::SeqCst) {
+                    tracing::warn!("MQTT connection lost: {}", e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -36,26 +71,20 @@ impl ChannelAdapter for MqttAdapter {
         "mqtt".to_string()
     }
 
+    fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Reports whether the client is connected to the broker.
+    async fn is_active(&self) -> Result<bool, Error> {
+        Ok(self.connected.load(Ordering::SeqCst))
+    }
+
     async fn send(&self, event: &Event) -> Result<(), Error> {
-        let payload = match serde_json::to_string(event) {
-            Ok(p) => p,
-            Err(e) => return Err(Error::Serde(Box::new(e))),
-        };
-        let mut attempt = 0;
-        loop {
-            match self
-                .client
-                .publish(&self.topic, QoS::AtLeastOnce, false, payload.clone())
-                .await
-            {
-                Ok(()) => return Ok(()),
-                Err(e) if attempt < self.max_retries => {
-                    attempt += 1;
-                    tracing::warn!("MQTT attempt {} failed: {}. Retrying...", attempt, e);
-                    sleep(Duration::from_secs(2u64.pow(attempt))).await;
-                }
-                Err(e) => return Err(Error::Mqtt(Box::new(e))),
-            }
-        }
+        let payload = event.to_payload(self.format, self.name_format)?;
+        self.client
+            .publish(&self.topic, QoS::AtLeastOnce, false, payload)
+            .await?;
+        Ok(())
     }
 }
@@ -0,0 +1,271 @@
+use crate::ChannelAdapter;
+use crate::Error;
+use crate::Event;
+use crate::adapter::table::Write;
+use crate::{MysqlConfig, TableFormat};
+use async_trait::async_trait;
+use mysql_async::prelude::Queryable;
+use mysql_async::{Conn, Opts, OptsBuilder, Pool, PoolConstraints, PoolOpts, TxOpts, Value};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// MySQL adapter for writing events to a table, in the namespace or access format.
+///
+/// The table is created on first use if missing, the event is stored as JSON in its serde
+/// representation.
+pub struct MysqlAdapter {
+    pool: Pool,
+    id: Option<String>,
+    table: String,
+    format: TableFormat,
+    timeout: Duration,
+    table_created: OnceCell<()>,
+}
+
+impl MysqlAdapter {
+    /// Creates a new MySQL adapter, failing if the DSN is invalid. Connections are opened on
+    /// demand.
+    pub fn new(config: &MysqlConfig) -> Result<Self, Error> {
+        let opts = Opts::from_url(&config.dsn)
+            .map_err(|e| Error::ConfigError(format!("Invalid MySQL dsn: {e}")))?;
+        let constraints = PoolConstraints::new(0, config.max_connections).ok_or_else(|| {
+            Error::ConfigError("MySQL max_connections must be greater than 0".to_string())
+        })?;
+        let opts = OptsBuilder::from_opts(opts)
+            .pool_opts(PoolOpts::default().with_constraints(constraints));
+
+        Ok(Self {
+            pool: Pool::new(opts),
+            id: config.target.id.clone(),
+            table: config.table.clone(),
+            format: config.table_format,
+            timeout: Duration::from_millis(config.timeout),
+            table_created: OnceCell::new(),
+        })
+    }
+
+    /// Takes a pooled connection, failing with a timed out I/O error if none can be opened within
+    /// the timeout.
+    async fn connection(&self) -> Result<Conn, Error> {
+        match tokio::time::timeout(self.timeout, self.pool.get_conn()).await {
+            Ok(connection) => Ok(connection?),
+            Err(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "MySQL connection timed out",
+            )
+            .into()),
+        }
+    }
+
+    /// Writes the events in a single transaction, in order.
+    async fn write(&self, events: &[Event]) -> Result<(), Error> {
+        let mut connection = self.connection().await?;
+        self.table_created
+            .get_or_try_init(|| async {
+                connection
+                    .query_drop(create_table(&self.table, self.format))
+                    .await?;
+                Ok::<_, Error>(())
+            })
+            .await?;
+
+        let mut transaction = connection.start_transaction(TxOpts::default()).await?;
+        for event in events {
+            let write = Write::of(self.format, event);
+            transaction
+                .exec_drop(statement(write, &self.table), params(write, event)?)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+/// Returns the statement creating `table` in the format.
+///
+/// Object keys of up to 1024 bytes do not fit the 3072 bytes InnoDB index limit in utf8mb4, so the
+/// namespace table is keyed by the bucket and the SHA-256 hash of the object key, a column stored
+/// by the server next to the full key.
+fn create_table(table: &str, format: TableFormat) -> String {
+    match format {
+        TableFormat::Namespace => format!(
+            "CREATE TABLE IF NOT EXISTS {table} (bucket VARCHAR(63) CHARACTER SET ascii NOT NULL, \
+             object_key TEXT NOT NULL, \
+             key_hash BINARY(32) AS (UNHEX(SHA2(object_key, 256))) STORED NOT NULL, \
+             event_time DATETIME(6) NOT NULL, event_data JSON NOT NULL, \
+             PRIMARY KEY (bucket, key_hash)) DEFAULT CHARSET = utf8mb4"
+        ),
+        TableFormat::Access => format!(
+            "CREATE TABLE IF NOT EXISTS {table} (id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY, \
+             event_time DATETIME(6) NOT NULL, event_data JSON NOT NULL) \
+             DEFAULT CHARSET = utf8mb4"
+        ),
+    }
+}
+
+/// Returns the statement of `write` to `table`, taking the [`params`] of the write.
+fn statement(write: Write, table: &str) -> String {
+    match write {
+        Write::Delete => {
+            format!("DELETE FROM {table} WHERE bucket = ? AND key_hash = UNHEX(SHA2(?, 256))")
+        }
+        Write::Upsert => format!(
+            "INSERT INTO {table} (bucket, object_key, event_time, event_data) \
+             VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE \
+             event_time = VALUES(event_time), event_data = VALUES(event_data)"
+        ),
+        Write::Append => format!("INSERT INTO {table} (event_time, event_data) VALUES (?, ?)"),
+    }
+}
+
+/// Returns the parameters of the statement of `write` for `event`, in order. The event is stored
+/// as its JSON text, which the server validates against the JSON column.
+fn params(write: Write, event: &Event) -> Result<Vec<Value>, Error> {
+    let bucket = Value::from(&event.s3.bucket.name);
+    let key = Value::from(&event.s3.object.key);
+    let params = match write {
+        Write::Delete => vec![bucket, key],
+        Write::Upsert => vec![
+            bucket,
+            key,
+            Value::from(event.occurred_at().naive_utc()),
+            Value::from(serde_json::to_string(event)?),
+        ],
+        Write::Append => vec![
+            Value::from(event.occurred_at().naive_utc()),
+            Value::from(serde_json::to_string(event)?),
+        ],
+    };
+    Ok(params)
+}
+
+#[async_trait]
+impl ChannelAdapter for MysqlAdapter {
+    fn name(&self) -> String {
+        "mysql".to_string()
+    }
+
+    fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Pings the server on a pooled connection, reporting the target inactive when it cannot be
+    /// reached.
+    async fn is_active(&self) -> Result<bool, Error> {
+        let mut connection = match self.connection().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::warn!("MySQL connection failed: {}", e);
+                return Ok(false);
+            }
+        };
+        match connection.ping().await {
+            Ok(()) => Ok(true),
+            Err(mysql_async::Error::Io(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        self.write(std::slice::from_ref(event)).await
+    }
+
+    /// Writes the events in a single transaction, so a failed batch leaves no partial writes.
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        self.write(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+    use crate::adapter::test_event;
+
+    fn mysql_config(dsn: &str, table: &str) -> MysqlConfig {
+        serde_json::from_value(serde_json::json!({
+            "dsn": dsn,
+            "table": table,
+            "max_retries": 1,
+            "timeout": 500,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_writes() {
+        let mut put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        put.event_time = "2024-05-01T10:00:00.250Z".to_string();
+        let removed = test_event(Name::ObjectRemovedDelete, "photos", "a.jpg");
+
+        // rows are found by the hash of their key, which the server derives from the stored key
+        assert_eq!(
+            statement(Write::Delete, "events"),
+            "DELETE FROM events WHERE bucket = ? AND key_hash = UNHEX(SHA2(?, 256))"
+        );
+        let table = create_table("events", TableFormat::Namespace);
+        assert!(table.contains("object_key TEXT NOT NULL"));
+        assert!(table.contains("key_hash BINARY(32) AS (UNHEX(SHA2(object_key, 256))) STORED"));
+        assert!(table.contains("PRIMARY KEY (bucket, key_hash)"));
+        assert!(statement(Write::Upsert, "events").contains("ON DUPLICATE KEY UPDATE"));
+        assert!(!statement(Write::Append, "events").contains("ON DUPLICATE KEY"));
+
+        assert_eq!(
+            super::params(Write::Delete, &removed).unwrap(),
+            vec![Value::from("photos"), Value::from("a.jpg")]
+        );
+        let params = super::params(Write::Upsert, &put).unwrap();
+        assert_eq!(params.len(), 4);
+        assert_eq!(params[2], Value::Date(2024, 5, 1, 10, 0, 0, 250_000));
+        assert_eq!(super::params(Write::Append, &put).unwrap()[..], params[2..]);
+    }
+
+    #[test]
+    fn test_event_data_is_json() {
+        let event = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let params = super::params(Write::Append, &event).unwrap();
+        let Value::Bytes(data) = &params[1] else {
+            panic!("event data is not sent as text");
+        };
+        let stored: Event = serde_json::from_slice(data).unwrap();
+        assert_eq!(stored.id, event.id);
+        assert!(create_table("events", TableFormat::Access).contains("event_data JSON NOT NULL"));
+    }
+
+    /// Writes to the database at `MYSQL_DSN` and reads the rows back.
+    #[tokio::test]
+    #[ignore = "needs a MySQL server at MYSQL_DSN"]
+    async fn test_mysql_round_trip() {
+        use mysql_async::prelude::Queryable;
+
+        let dsn = std::env::var("MYSQL_DSN").unwrap_or("mysql://root@127.0.0.1/test".to_string());
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let table = format!("events_{}", put.id.simple());
+        let mut config = mysql_config(&dsn, &table);
+        config.timeout = 5000;
+        let adapter = MysqlAdapter::new(&config).unwrap();
+        assert!(adapter.is_active().await.unwrap());
+
+        let events = [
+            put.clone(),
+            test_event(Name::ObjectCreatedPut, "photos", "b.jpg"),
+            test_event(Name::ObjectRemovedDelete, "photos", "b.jpg"),
+        ];
+        adapter.send_batch(&events).await.unwrap();
+
+        let mut connection = adapter.connection().await.unwrap();
+        let rows: Vec<(String, String)> = connection
+            .query(format!("SELECT object_key, event_data FROM {table}"))
+            .await
+            .unwrap();
+        connection
+            .query_drop(format!("DROP TABLE {table}"))
+            .await
+            .unwrap();
+        // the removal deleted the row of b.jpg
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, "a.jpg");
+        let stored: Event = serde_json::from_str(&rows[0].1).unwrap();
+        assert_eq!(stored.id, put.id);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::ChannelAdapter;
+use crate::Error;
+use crate::Event;
+use crate::NatsConfig;
+use async_nats::connection::State;
+use async_nats::{Client, ConnectOptions, jetstream};
+use async_trait::async_trait;
+use std::future::IntoFuture;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// NATS adapter for publishing events to NATS subjects, optionally through JetStream.
+///
+/// The connection is opened on first use and kept up by the client, which reconnects on its own.
+pub struct NatsAdapter {
+    config: NatsConfig,
+    client: OnceCell<Client>,
+}
+
+impl NatsAdapter {
+    /// Creates a new NATS adapter.
+    pub fn new(config: NatsConfig) -> Self {
+        Self {
+            config,
+            client: OnceCell::new(),
+        }
+    }
+
+    /// Returns the client, connecting to the servers if not done yet.
+    async fn client(&self) -> Result<&Client, Error> {
+        self.client
+            .get_or_try_init(|| async {
+                let url = self.config.url.as_str();
+                let client = self.connect_options().await?.connect(url).await?;
+                Ok::<_, Error>(client)
+            })
+            .await
+    }
+
+    /// Builds the connection options from the credentials and TLS settings of the config.
+    async fn connect_options(&self) -> Result<ConnectOptions, Error> {
+        let config = &self.config;
+        let mut options = match (&config.credentials_file, &config.nkey, &config.token) {
+            (Some(path), _, _) => ConnectOptions::with_credentials_file(path).await?,
+            (_, Some(seed), _) => ConnectOptions::with_nkey(seed.clone()),
+            (_, _, Some(token)) => ConnectOptions::with_token(token.clone()),
+            _ => ConnectOptions::new(),
+        };
+        if let (Some(user), Some(password)) = (&config.username, &config.password) {
+            options = options.user_and_password(user.clone(), password.clone());
+        }
+        if let Some(ca) = &config.tls_ca_file {
+            options = options.add_root_certificates(PathBuf::from(ca));
+        }
+        if let (Some(cert), Some(key)) = (&config.tls_cert_file, &config.tls_key_file) {
+            options = options.add_client_certificate(PathBuf::from(cert), PathBuf::from(key));
+        }
+        let timeout = Duration::from_millis(config.timeout);
+        Ok(options
+            .require_tls(config.tls_required)
+            .connection_timeout(timeout)
+            .request_timeout(Some(timeout)))
+    }
+
+    /// Publishes the events, waiting for JetStream to acknowledge each of them when enabled.
+    async fn publish(&self, events: &[Event]) -> Result<(), Error> {
+        let messages = messages(&self.config, events)?;
+        let client = self.client().await?;
+        if self.config.jetstream {
+            let mut context = jetstream::new(client.clone());
+            context.set_timeout(Duration::from_millis(self.config.timeout));
+            let mut acks = Vec::with_capacity(messages.len());
+            for (subject, payload) in messages {
+                acks.push(context.publish(subject, payload.into()).await?);
+            }
+            await_acks(acks).await
+        } else {
+            for (subject, payload) in messages {
+                client.publish(subject, payload.into()).await?;
+            }
+            // core publishes are buffered, flushing reports a lost connection to the caller
+            client.flush().await?;
+            Ok(())
+        }
+    }
+}
+
+/// Returns the subject and payload of the message publishing each event.
+fn messages(config: &NatsConfig, events: &[Event]) -> Result<Vec<(String, String)>, Error> {
+    events
+        .iter()
+        .map(|event| {
+            let payload = event.to_payload(config.target.format, config.target.name_format)?;
+            Ok((config.subject_for(event), payload))
+        })
+        .collect()
+}
+
+/// Waits for every JetStream acknowledgement, failing with the first error once all of them are
+/// settled.
+async fn await_acks<A, T, E>(acks: Vec<A>) -> Result<(), Error>
+where
+    A: IntoFuture<Output = Result<T, E>>,
+    Error: From<E>,
+{
+    let mut result = Ok(());
+    for ack in acks {
+        if let Err(e) = ack.await
+            && result.is_ok()
+        {
+            result = Err(e.into());
+        }
+    }
+    result
+}
+
+#[async_trait]
+impl ChannelAdapter for NatsAdapter {
+    fn name(&self) -> String {
+        "nats".to_string()
+    }
+
+    fn id(&self) -> String {
+        self.config.target.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Reports whether the client is connected, connecting first if not done yet.
+    async fn is_active(&self) -> Result<bool, Error> {
+        match self.client().await {
+            Ok(client) => Ok(client.connection_state() == State::Connected),
+            Err(Error::Nats(e)) => {
+                tracing::warn!("NATS connection failed: {}", e);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        self.publish(std::slice::from_ref(event)).await
+    }
+
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        self.publish(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::test_event;
+    use crate::{Name, NameFormat, PayloadFormat};
+    use async_nats::jetstream::context::{PublishError, PublishErrorKind};
+    use std::future::ready;
+
+    fn nats_config(subject: &str) -> NatsConfig {
+        serde_json::from_value(serde_json::json!({
+            "url": "nats://127.0.0.1:4222",
+            "subject": subject,
+            "max_retries": 1,
+            "timeout": 500,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut config = nats_config("events.{bucket}.{event_name}.{key}");
+        config.target.format = PayloadFormat::Native;
+        config.target.name_format = NameFormat::S3;
+        let events = [
+            test_event(Name::ObjectCreatedPut, "photos", "2024/red flower*.jpg"),
+            test_event(Name::ObjectRemovedDelete, "photos", "a.jpg"),
+        ];
+
+        let messages = messages(&config, &events).unwrap();
+        let subjects: Vec<&str> = messages.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(
+            subjects,
+            vec![
+                "events.photos.s3:ObjectCreated:Put.2024/red_flower_.jpg",
+                "events.photos.s3:ObjectRemoved:Delete.a.jpg",
+            ]
+        );
+        let payload: serde_json::Value = serde_json::from_str(&messages[0].1).unwrap();
+        assert_eq!(payload["eventName"], "s3:ObjectCreated:Put");
+        assert_eq!(payload["id"], events[0].id.to_string());
+
+        let config = nats_config("events");
+        assert_eq!(config.subject_for(&events[0]), "events");
+    }
+
+    #[tokio::test]
+    async fn test_jetstream_acks() {
+        let acks = vec![ready(Ok::<_, PublishError>(())), ready(Ok(()))];
+        assert!(await_acks(acks).await.is_ok());
+
+        // a missing acknowledgement fails the publish, even when later ones arrive
+        let acks = vec![
+            ready(Ok(())),
+            ready(Err(PublishError::from(PublishErrorKind::TimedOut))),
+            ready(Ok(())),
+        ];
+        let result = await_acks(acks).await;
+        assert!(matches!(result, Err(ref e @ Error::Nats(_)) if e.is_retryable()));
+    }
+
+    /// Publishes to the server at `NATS_URL` and reads the messages back.
+    #[tokio::test]
+    #[ignore = "needs a NATS server at NATS_URL"]
+    async fn test_nats_round_trip() {
+        use tokio_stream::StreamExt;
+
+        let url = std::env::var("NATS_URL").unwrap_or("nats://127.0.0.1:4222".to_string());
+        let client = async_nats::connect(&url).await.unwrap();
+        let mut subscriber = client.subscribe("events.photos").await.unwrap();
+        client.flush().await.unwrap();
+
+        let mut config = nats_config("events.{bucket}");
+        config.url = url;
+        let adapter = NatsAdapter::new(config);
+        assert!(adapter.is_active().await.unwrap());
+        let events = [
+            test_event(Name::ObjectCreatedPut, "photos", "a.jpg"),
+            test_event(Name::ObjectRemovedDelete, "photos", "a.jpg"),
+        ];
+        adapter.send_batch(&events).await.unwrap();
+
+        for event in &events {
+            let message = subscriber.next().await.unwrap();
+            let received: Event = serde_json::from_slice(&message.payload).unwrap();
+            assert_eq!(received.id, event.id);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use crate::ChannelAdapter;
+use crate::Error;
+use crate::Event;
+use crate::adapter::table::Write;
+use crate::{PostgresConfig, TableFormat};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{Manager, Pool, Runtime, Timeouts};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio_postgres::types::{Json, ToSql};
+
+/// PostgreSQL adapter for writing events to a table, in the namespace or access format.
+///
+/// The table is created on first use if missing, the event is stored as JSONB in its serde
+/// representation.
+pub struct PostgresAdapter {
+    pool: Pool,
+    id: Option<String>,
+    table: String,
+    format: TableFormat,
+    table_created: OnceCell<()>,
+}
+
+impl PostgresAdapter {
+    /// Creates a new PostgreSQL adapter, failing if the connection string is invalid. Connections
+    /// are opened on demand.
+    pub fn new(config: &PostgresConfig) -> Result<Self, Error> {
+        let timeout = Duration::from_millis(config.timeout);
+        let mut pg_config = tokio_postgres::Config::from_str(&config.dsn)?;
+        pg_config.connect_timeout(timeout);
+        let pool = Pool::builder(Manager::new(pg_config, tokio_postgres::NoTls))
+            .max_size(config.max_connections)
+            .timeouts(Timeouts {
+                wait: Some(timeout),
+                create: Some(timeout),
+                recycle: Some(timeout),
+            })
+            .runtime(Runtime::Tokio1)
+            .build()
+            .map_err(|e| Error::ConfigError(e.to_string()))?;
+
+        Ok(Self {
+            pool,
+            id: config.target.id.clone(),
+            table: config.table.clone(),
+            format: config.table_format,
+            table_created: OnceCell::new(),
+        })
+    }
+
+    /// Writes the events in a single transaction, in order.
+    async fn write(&self, events: &[Event]) -> Result<(), Error> {
+        let mut client = self.pool.get().await?;
+        self.table_created
+            .get_or_try_init(|| async {
+                client
+                    .batch_execute(&create_table(&self.table, self.format))
+                    .await?;
+                Ok::<_, Error>(())
+            })
+            .await?;
+
+        let transaction = client.transaction().await?;
+        for event in events {
+            let write = Write::of(self.format, event);
+            let statement = transaction
+                .prepare_cached(&statement(write, &self.table))
+                .await?;
+            transaction
+                .execute(&statement, &Row::new(event).params(write))
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+/// Returns the statement creating `table` in the format.
+fn create_table(table: &str, format: TableFormat) -> String {
+    match format {
+        TableFormat::Namespace => format!(
+            "CREATE TABLE IF NOT EXISTS {table} (bucket TEXT NOT NULL, object_key TEXT NOT NULL, \
+             event_time TIMESTAMPTZ NOT NULL, event_data JSONB NOT NULL, \
+             PRIMARY KEY (bucket, object_key))"
+        ),
+        TableFormat::Access => format!(
+            "CREATE TABLE IF NOT EXISTS {table} (id BIGSERIAL PRIMARY KEY, \
+             event_time TIMESTAMPTZ NOT NULL, event_data JSONB NOT NULL)"
+        ),
+    }
+}
+
+/// Returns the statement of `write` to `table`, taking the [`Row::params`] of the write.
+fn statement(write: Write, table: &str) -> String {
+    match write {
+        Write::Delete => format!("DELETE FROM {table} WHERE bucket = $1 AND object_key = $2"),
+        Write::Upsert => format!(
+            "INSERT INTO {table} (bucket, object_key, event_time, event_data) \
+             VALUES ($1, $2, $3, $4) ON CONFLICT (bucket, object_key) DO UPDATE \
+             SET event_time = EXCLUDED.event_time, event_data = EXCLUDED.event_data"
+        ),
+        Write::Append => format!("INSERT INTO {table} (event_time, event_data) VALUES ($1, $2)"),
+    }
+}
+
+/// Column values of an event.
+struct Row<'a> {
+    bucket: &'a str,
+    key: &'a str,
+    event_time: DateTime<Utc>,
+    event_data: Json<&'a Event>,
+}
+
+impl<'a> Row<'a> {
+    fn new(event: &'a Event) -> Self {
+        Self {
+            bucket: &event.s3.bucket.name,
+            key: &event.s3.object.key,
+            event_time: event.occurred_at(),
+            event_data: Json(event),
+        }
+    }
+
+    /// Returns the parameters of the statement of `write`, in order.
+    fn params(&self, write: Write) -> Vec<&(dyn ToSql + Sync)> {
+        match write {
+            Write::Delete => vec![&self.bucket, &self.key],
+            Write::Upsert => vec![&self.bucket, &self.key, &self.event_time, &self.event_data],
+            Write::Append => vec![&self.event_time, &self.event_data],
+        }
+    }
+}
+
+#[async_trait]
+impl ChannelAdapter for PostgresAdapter {
+    fn name(&self) -> String {
+        "postgres".to_string()
+    }
+
+    fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Runs `SELECT 1` on a pooled connection, reporting the target inactive when the server
+    /// cannot be reached.
+    async fn is_active(&self) -> Result<bool, Error> {
+        let client = match self.pool.get().await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("PostgreSQL connection failed: {}", e);
+                return Ok(false);
+            }
+        };
+        match client.simple_query("SELECT 1").await {
+            Ok(_) => Ok(true),
+            Err(e) if e.is_closed() => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        self.write(std::slice::from_ref(event)).await
+    }
+
+    /// Writes the events in a single transaction, so a failed batch leaves no partial writes.
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        self.write(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+    use crate::adapter::test_event;
+    use bytes::BytesMut;
+    use tokio_postgres::types::Type;
+
+    fn postgres_config(dsn: &str, table: &str) -> PostgresConfig {
+        serde_json::from_value(serde_json::json!({
+            "dsn": dsn,
+            "table": table,
+            "table_format": "access",
+            "max_retries": 1,
+            "timeout": 500,
+        }))
+        .unwrap()
+    }
+
+    /// Encodes `param` as a value of type `ty`, failing if the type does not accept it.
+    fn encode(param: &(dyn ToSql + Sync), ty: &Type) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        param.to_sql_checked(ty, &mut buf).unwrap();
+        buf.to_vec()
+    }
+
+    #[test]
+    fn test_writes() {
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+
+        assert_eq!(
+            statement(Write::Delete, "events"),
+            "DELETE FROM events WHERE bucket = $1 AND object_key = $2"
+        );
+        assert!(
+            statement(Write::Upsert, "events")
+                .contains("ON CONFLICT (bucket, object_key) DO UPDATE")
+        );
+        assert!(!statement(Write::Append, "events").contains("ON CONFLICT"));
+        assert!(
+            create_table("events", TableFormat::Namespace)
+                .contains("PRIMARY KEY (bucket, object_key)")
+        );
+
+        let row = Row::new(&put);
+        assert_eq!(row.params(Write::Delete).len(), 2);
+        assert_eq!(row.params(Write::Append).len(), 2);
+        let params = row.params(Write::Upsert);
+        assert_eq!(params.len(), 4);
+        assert_eq!(encode(params[0], &Type::TEXT), b"photos");
+        assert_eq!(encode(params[1], &Type::TEXT), b"a.jpg");
+        encode(params[2], &Type::TIMESTAMPTZ);
+    }
+
+    #[test]
+    fn test_event_data_is_jsonb() {
+        let event = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let row = Row::new(&event);
+        let params = row.params(Write::Append);
+
+        // JSONB values are prefixed by their format version
+        let data = encode(params[1], &Type::JSONB);
+        assert_eq!(data[0], 1);
+        let stored: Event = serde_json::from_slice(&data[1..]).unwrap();
+        assert_eq!(stored.id, event.id);
+        assert_eq!(stored.event_name, Name::ObjectCreatedPut);
+        assert!(create_table("events", TableFormat::Access).contains("event_data JSONB NOT NULL"));
+    }
+
+    /// Writes to the database at `POSTGRES_DSN` and reads the rows back.
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL server at POSTGRES_DSN"]
+    async fn test_postgres_round_trip() {
+        let dsn = std::env::var("POSTGRES_DSN")
+            .unwrap_or("host=127.0.0.1 user=postgres dbname=postgres".to_string());
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let table = format!("events_{}", put.id.simple());
+        let mut config = postgres_config(&dsn, &table);
+        config.table_format = TableFormat::Namespace;
+        config.timeout = 5000;
+        let adapter = PostgresAdapter::new(&config).unwrap();
+        assert!(adapter.is_active().await.unwrap());
+
+        let events = [
+            put.clone(),
+            test_event(Name::ObjectCreatedPut, "photos", "b.jpg"),
+            test_event(Name::ObjectRemovedDelete, "photos", "b.jpg"),
+        ];
+        adapter.send_batch(&events).await.unwrap();
+
+        let client = adapter.pool.get().await.unwrap();
+        let rows = client
+            .query(&format!("SELECT object_key, event_data FROM {table}"), &[])
+            .await
+            .unwrap();
+        client
+            .batch_execute(&format!("DROP TABLE {table}"))
+            .await
+            .unwrap();
+        // the removal deleted the row of b.jpg
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get::<_, &str>(0), "a.jpg");
+        let Json(stored): Json<Event> = rows[0].get(1);
+        assert_eq!(stored.id, put.id);
+    }
+}
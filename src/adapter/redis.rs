@@ -0,0 +1,245 @@
+use crate::ChannelAdapter;
+use crate::Error;
+use crate::Event;
+use crate::Name;
+use crate::{RedisConfig, RedisMode};
+use async_trait::async_trait;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::streams::StreamMaxlen;
+use std::time::Duration;
+
+/// Number of times a lost connection is reopened before a command fails, the event bus retries
+/// the delivery itself.
+const RECONNECT_RETRIES: usize = 1;
+
+/// Stream entry field holding the event payload in `stream` mode.
+const STREAM_FIELD: &str = "event";
+
+/// Redis adapter for writing events to a stream, channel, hash or list.
+pub struct RedisAdapter {
+    config: RedisConfig,
+    connection: ConnectionManager,
+}
+
+impl RedisAdapter {
+    /// Creates a new Redis adapter, the connection is opened on first use and reopened when lost.
+    pub fn new(config: &RedisConfig) -> Result<Self, Error> {
+        let timeout = Some(Duration::from_millis(config.timeout));
+        let connection = redis::Client::open(config.url.as_str())?.get_connection_manager_lazy(
+            ConnectionManagerConfig::new()
+                .set_number_of_retries(RECONNECT_RETRIES)
+                .set_connection_timeout(timeout)
+                .set_response_timeout(timeout),
+        )?;
+
+        Ok(Self {
+            config: config.clone(),
+            connection,
+        })
+    }
+
+    /// Writes the events in a single round trip, in order.
+    async fn write(&self, events: &[Event]) -> Result<(), Error> {
+        let mut pipe = redis::pipe();
+        for event in events {
+            pipe.add_command(command(&self.config, event)?).ignore();
+        }
+        pipe.query_async::<()>(&mut self.connection.clone()).await?;
+        Ok(())
+    }
+}
+
+/// Returns the command writing `event` in the mode of the config.
+fn command(config: &RedisConfig, event: &Event) -> Result<redis::Cmd, Error> {
+    let key = &config.key;
+    let payload = event.to_payload(config.target.format, config.target.name_format)?;
+    let command = match config.mode {
+        RedisMode::Stream => match config.max_len {
+            Some(max_len) => redis::Cmd::xadd_maxlen(
+                key,
+                StreamMaxlen::Approx(max_len),
+                "*",
+                &[(STREAM_FIELD, payload)],
+            ),
+            None => redis::Cmd::xadd(key, "*", &[(STREAM_FIELD, payload)]),
+        },
+        RedisMode::Publish => redis::Cmd::publish(key, payload),
+        RedisMode::Namespace => {
+            let field = format!("{}/{}", event.s3.bucket.name, event.s3.object.key);
+            if event.is_type(Name::ObjectRemovedAll) {
+                redis::Cmd::hdel(key, field)
+            } else {
+                redis::Cmd::hset(key, field, payload)
+            }
+        }
+        RedisMode::Access => redis::Cmd::rpush(key, payload),
+    };
+    Ok(command)
+}
+
+#[async_trait]
+impl ChannelAdapter for RedisAdapter {
+    fn name(&self) -> String {
+        "redis".to_string()
+    }
+
+    fn id(&self) -> String {
+        self.config.target.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Sends a `PING`, reporting the target inactive when the server cannot be reached.
+    async fn is_active(&self) -> Result<bool, Error> {
+        let ping = redis::cmd("PING")
+            .query_async::<String>(&mut self.connection.clone())
+            .await;
+        match ping {
+            Ok(_) => Ok(true),
+            Err(e) if e.is_io_error() || e.is_timeout() || e.is_connection_dropped() => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        self.write(std::slice::from_ref(event)).await
+    }
+
+    /// Sends the events in a single pipeline, applied in order by the server.
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        self.write(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::test_event;
+    use redis::Arg;
+    use std::collections::HashMap;
+
+    fn redis_config(mode: &str) -> RedisConfig {
+        serde_json::from_value(serde_json::json!({
+            "url": "redis://127.0.0.1:6379",
+            "key": "events",
+            "mode": mode,
+            "max_retries": 1,
+            "timeout": 500,
+        }))
+        .unwrap()
+    }
+
+    /// Returns the arguments of the command writing `event`, the payload replaced by `<payload>`.
+    fn args(config: &RedisConfig, event: &Event) -> Vec<String> {
+        command(config, event)
+            .unwrap()
+            .args_iter()
+            .filter_map(|arg| match arg {
+                Arg::Simple(arg) => Some(String::from_utf8_lossy(arg).into_owned()),
+                _ => None,
+            })
+            .map(|arg| match serde_json::from_str::<Event>(&arg) {
+                Ok(payload) if payload.id == event.id => "<payload>".to_string(),
+                _ => arg,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_commands() {
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let removed = test_event(Name::ObjectRemovedDelete, "photos", "a.jpg");
+
+        let mut config = redis_config("stream");
+        assert_eq!(
+            args(&config, &put),
+            ["XADD", "events", "*", "event", "<payload>"]
+        );
+        config.max_len = Some(1000);
+        assert_eq!(
+            args(&config, &put),
+            [
+                "XADD",
+                "events",
+                "MAXLEN",
+                "~",
+                "1000",
+                "*",
+                "event",
+                "<payload>"
+            ]
+        );
+
+        let config = redis_config("publish");
+        assert_eq!(args(&config, &put), ["PUBLISH", "events", "<payload>"]);
+
+        // the hash keeps the latest event of every object and forgets removed ones
+        let config = redis_config("namespace");
+        assert_eq!(
+            args(&config, &put),
+            ["HSET", "events", "photos/a.jpg", "<payload>"]
+        );
+        assert_eq!(args(&config, &removed), ["HDEL", "events", "photos/a.jpg"]);
+
+        let config = redis_config("access");
+        assert_eq!(args(&config, &removed), ["RPUSH", "events", "<payload>"]);
+    }
+
+    /// Writes to the server at `REDIS_URL` in the namespace and access modes and reads the events
+    /// back.
+    #[tokio::test]
+    #[ignore = "needs a Redis server at REDIS_URL"]
+    async fn test_redis_round_trip() {
+        let url = std::env::var("REDIS_URL").unwrap_or("redis://127.0.0.1:6379".to_string());
+        let mut connection = redis::Client::open(url.as_str())
+            .unwrap()
+            .get_multiplexed_async_connection()
+            .await
+            .unwrap();
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let removed = test_event(Name::ObjectRemovedDelete, "photos", "b.jpg");
+
+        let mut config = redis_config("namespace");
+        config.url = url.clone();
+        config.key = format!("events-{}", put.id);
+        let adapter = RedisAdapter::new(&config).unwrap();
+        assert!(adapter.is_active().await.unwrap());
+        adapter
+            .send_batch(&[put.clone(), removed.clone()])
+            .await
+            .unwrap();
+        let stored: HashMap<String, String> = redis::cmd("HGETALL")
+            .arg(&config.key)
+            .query_async(&mut connection)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        let event: Event = serde_json::from_str(&stored["photos/a.jpg"]).unwrap();
+        assert_eq!(event.id, put.id);
+
+        config.mode = RedisMode::Access;
+        config.key = format!("events-{}", removed.id);
+        let adapter = RedisAdapter::new(&config).unwrap();
+        adapter
+            .send_batch(&[put.clone(), removed.clone()])
+            .await
+            .unwrap();
+        let stored: Vec<String> = redis::cmd("LRANGE")
+            .arg(&config.key)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut connection)
+            .await
+            .unwrap();
+        let ids: Vec<_> = stored
+            .iter()
+            .map(|payload| serde_json::from_str::<Event>(payload).unwrap().id)
+            .collect();
+        assert_eq!(ids, vec![put.id, removed.id]);
+
+        let _: () = redis::cmd("DEL")
+            .arg(format!("events-{}", put.id))
+            .arg(&config.key)
+            .query_async(&mut connection)
+            .await
+            .unwrap();
+    }
+}
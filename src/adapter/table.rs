@@ -0,0 +1,44 @@
+use crate::Event;
+use crate::Name;
+use crate::TableFormat;
+
+/// How a single event is written to a table of the namespace or access format, each database
+/// adapter providing the statement and parameters of the writes in its own SQL dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Write {
+    /// remove the row of the object
+    Delete,
+    /// insert the row of the object or replace it
+    Upsert,
+    /// insert a new row
+    Append,
+}
+
+impl Write {
+    /// Returns how `event` is written to a table of the format, removal events deleting the row of
+    /// their object in the namespace format.
+    pub(crate) fn of(format: TableFormat, event: &Event) -> Self {
+        match format {
+            TableFormat::Namespace if event.is_type(Name::ObjectRemovedAll) => Write::Delete,
+            TableFormat::Namespace => Write::Upsert,
+            TableFormat::Access => Write::Append,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::test_event;
+
+    #[test]
+    fn test_write_of() {
+        let put = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+        let removed = test_event(Name::ObjectRemovedDelete, "photos", "a.jpg");
+
+        assert_eq!(Write::of(TableFormat::Namespace, &put), Write::Upsert);
+        assert_eq!(Write::of(TableFormat::Namespace, &removed), Write::Delete);
+        assert_eq!(Write::of(TableFormat::Access, &put), Write::Append);
+        assert_eq!(Write::of(TableFormat::Access, &removed), Write::Append);
+    }
+}
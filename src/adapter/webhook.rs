@@ -3,9 +3,9 @@ use crate::Error;
 use crate::Event;
 use crate::WebhookConfig;
 use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
 use reqwest::{Client, RequestBuilder};
 use std::time::Duration;
-use tokio::time::sleep;
 
 /// Webhook adapter for sending events to a webhook endpoint.
 pub struct WebhookAdapter {
@@ -23,8 +23,12 @@ impl WebhookAdapter {
         Self { config, client }
     }
     /// Builds the request to send the event.
-    fn build_request(&self, event: &Event) -> RequestBuilder {
-        let mut request = self.client.post(&self.config.endpoint).json(event);
+    fn build_request(&self, payload: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .post(&self.config.endpoint)
+            .header(CONTENT_TYPE, "application/json")
+            .body(payload.to_string());
         if let Some(token) = &self.config.auth_token {
             request = request.header("Authorization", format!("Bearer {token}"));
         }
@@ -35,6 +39,16 @@ impl WebhookAdapter {
         }
         request
     }
+    /// Posts the payload to the endpoint.
+    async fn post(&self, payload: &str) -> Result<(), Error> {
+        let response = self.build_request(payload).send().await?;
+        if let Err(e) = response.error_for_status() {
+            tracing::error!("Webhook failed with status: {}", e);
+            return Err(Error::Http(Box::new(e)));
+        }
+        tracing::info!("Webhook sent successfully");
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -43,27 +57,40 @@ impl ChannelAdapter for WebhookAdapter {
         "webhook".to_string()
     }
 
-    async fn send(&self, event: &Event) -> Result<(), Error> {
-        let mut attempt = 0;
-        loop {
-            match self.build_request(event).send().await {
-                Ok(response) => {
-                    match response.error_for_status() {
-                        Ok(_) => tracing::info!("Webhook sent successfully"),
-                        Err(e) => {
-                            tracing::error!("Webhook failed with status: {}", e);
-                            return Err(Error::Http(Box::new(e)));
-                        }
-                    }
-                    return Ok(());
-                }
-                Err(e) if attempt < self.config.max_retries => {
-                    attempt += 1;
-                    tracing::warn!("Webhook attempt {} failed: {}. Retrying...", attempt, e);
-                    sleep(Duration::from_secs(2u64.pow(attempt))).await;
-                }
-                Err(e) => return Err(Error::Http(Box::new(e))),
+    fn id(&self) -> String {
+        self.config.target.id.clone().unwrap_or_else(|| self.name())
+    }
+
+    /// Sends a `HEAD` request to the endpoint; any response short of a server error counts as active,
+    /// as does the 501 of servers that do not implement `HEAD`.
+    async fn is_active(&self) -> Result<bool, Error> {
+        let mut request = self.client.head(&self.config.endpoint);
+        if let Some(token) = &self.config.auth_token {
+            request = request.header("Authorization", format!("Bearer {token}"));
+        }
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                Ok(!status.is_server_error() || status == reqwest::StatusCode::NOT_IMPLEMENTED)
             }
+            Err(e) if e.is_connect() || e.is_timeout() => Ok(false),
+            Err(e) => Err(Error::Http(Box::new(e))),
         }
     }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        let payload =
+            event.to_payload(self.config.target.format, self.config.target.name_format)?;
+        self.post(&payload).await
+    }
+
+    /// Posts the events in a single request, as a JSON array or a single S3 `Records` envelope.
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        let payload = Event::to_batch_payload(
+            events,
+            self.config.target.format,
+            self.config.target.name_format,
+        )?;
+        self.post(&payload).await
+    }
 }
@@ -0,0 +1,65 @@
+use crate::Error;
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt;
+use std::str::FromStr;
+
+const ARN_PREFIX: &str = "arn";
+const ARN_SERVICE: &str = "sqs";
+
+/// The ARN of a notification target, e.g. `arn:rustfs:sqs:us-east-1:primary:webhook`.
+///
+/// `id` distinguishes several targets of the same kind while `name` is the kind of adapter the
+/// target is delivered by, as returned by [`ChannelAdapter::name`](crate::ChannelAdapter::name).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SerializeDisplay, DeserializeFromStr)]
+pub struct TargetArn {
+    pub partition: String,
+    pub region: String,
+    pub id: String,
+    pub name: String,
+}
+
+impl TargetArn {
+    /// Creates an ARN in the `rustfs` partition.
+    pub fn new(region: impl Into<String>, id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            partition: "rustfs".to_string(),
+            region: region.into(),
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl fmt::Display for TargetArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{ARN_PREFIX}:{}:{ARN_SERVICE}:{}:{}:{}",
+            self.partition, self.region, self.id, self.name
+        )
+    }
+}
+
+impl FromStr for TargetArn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::ConfigError(format!("invalid target ARN: {s}"));
+        let parts: Vec<&str> = s.split(':').collect();
+        let [prefix, partition, service, region, id, name] = parts[..] else {
+            return Err(invalid());
+        };
+        if prefix != ARN_PREFIX || service != ARN_SERVICE {
+            return Err(invalid());
+        }
+        if partition.is_empty() || id.is_empty() || name.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            partition: partition.to_string(),
+            region: region.to_string(),
+            id: id.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
@@ -1,68 +1,676 @@
+use crate::BatchConfig;
 use crate::ChannelAdapter;
 use crate::Error;
-use crate::EventStore;
+use crate::EventStorage;
+use crate::QueueStore;
+use crate::RetryPolicy;
+use crate::RulesMap;
+use crate::TargetConfig;
+use crate::{DeadLetter, DeadLetterStore};
 use crate::{Event, Log};
-use chrono::Utc;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, Semaphore, mpsc};
 use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Interval at which the events of an unreachable adapter are retried, from its persistent queue or
+/// from the events its delivery workers parked.
+const QUEUE_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Interval at which a full persistent queue is checked for room again.
+const QUEUE_FULL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A persistent queue and the signal used to wake up its replayer.
+struct Queue {
+    store: Arc<QueueStore>,
+    notify: Arc<Notify>,
+}
+
+/// An event on its way to a delivery worker, with the dead letter it was requeued from.
+struct Delivery {
+    event: Arc<Event>,
+    dead_letter: Option<Uuid>,
+}
+
+/// A delivery rejected because its adapter is unavailable, kept by the ids its event is reloaded by.
+struct Parked {
+    event: Uuid,
+    dead_letter: Option<Uuid>,
+}
+
+/// How events reach an adapter: through its persistent queue or through the channel feeding its
+/// delivery workers.
+enum Route {
+    Queue(Queue),
+    Workers(mpsc::Sender<Delivery>),
+}
+
+/// Everything needed to deliver events to one adapter.
+#[derive(Clone)]
+struct Deliverer {
+    adapter: Arc<dyn ChannelAdapter>,
+    retry: RetryPolicy,
+    batch: Option<BatchConfig>,
+    store: Arc<dyn EventStorage>,
+    dead_letters: Option<Arc<DeadLetterStore>>,
+}
 
 /// Handles incoming events from the producer.
 ///
 /// This function is responsible for receiving events from the producer and sending them to the appropriate adapters.
-/// It also handles the shutdown process and saves any pending logs to the event store.
+/// Each event goes to the targets of every notification rule in `rules` it matches, plus any adapter whose id
+/// is listed in the event channels.
+/// Every event is recorded in the event storage before it is dispatched and acknowledged per adapter once
+/// delivered, so events left undelivered by a previous run are replayed when the bus starts.
+///
+/// `targets` holds the settings of the adapters by id, adapters without an entry use the defaults. Each adapter
+/// is fed by its own bounded channel drained by up to `concurrency` delivery workers, so a slow adapter never
+/// delays the others; the bus only waits on an adapter once `channel_capacity` events are waiting for it.
+/// Adapters with a `queue_dir` own a persistent queue instead: their events are written to it before any send
+/// attempt and delivered by a background replayer that keeps retrying while the adapter is unreachable.
+/// Adapters with a `batch` setting are sent their events in batches through `ChannelAdapter::send_batch`.
+///
+/// Deliveries that fail for good are recorded in `dead_letters` and acknowledged, without a dead-letter store
+/// they stay pending until the next start. Dead letters requeued through the store are delivered again.
+/// Events rejected because the circuit of their target is open or it failed its health check are never
+/// dead-lettered: they stay in the persistent queue, or are parked by the delivery workers, which keep
+/// only their ids and reload them from the event storage or the dead-letter store to retry them until
+/// the target accepts them. An unavailable target thus never holds up the event bus.
 pub async fn event_bus(
     mut rx: mpsc::Receiver<Event>,
     adapters: Vec<Arc<dyn ChannelAdapter>>,
-    store: Arc<EventStore>,
+    store: Arc<dyn EventStorage>,
+    targets: HashMap<String, TargetConfig>,
+    rules: Arc<RulesMap>,
+    dead_letters: Option<Arc<DeadLetterStore>>,
     shutdown: CancellationToken,
 ) -> Result<(), Error> {
-    let mut pending_logs = Vec::new();
-    let mut current_log = Log {
-        event_name: crate::event::Name::Everything,
-        key: Utc::now().timestamp().to_string(),
-        records: Vec::new(),
-    };
+    let mut routes = HashMap::new();
+    for adapter in &adapters {
+        let id = adapter.id();
+        let config = targets.get(&id).cloned().unwrap_or_default();
+        config.validate().map_err(Error::ConfigError)?;
+        let deliverer = Deliverer {
+            adapter: adapter.clone(),
+            retry: config.retry.clone().unwrap_or_default(),
+            batch: config.batch.clone(),
+            store: store.clone(),
+            dead_letters: dead_letters.clone(),
+        };
+        let route = match &config.queue_dir {
+            Some(queue_dir) => {
+                let queue_store = Arc::new(QueueStore::new(queue_dir, config.queue_limit).await?);
+                let notify = Arc::new(Notify::new());
+                tokio::spawn(replay_queue(
+                    deliverer,
+                    queue_store.clone(),
+                    notify.clone(),
+                    shutdown.clone(),
+                ));
+                Route::Queue(Queue {
+                    store: queue_store,
+                    notify,
+                })
+            }
+            None => {
+                let (tx, rx) = mpsc::channel(config.channel_capacity);
+                tokio::spawn(run_workers(
+                    deliverer,
+                    rx,
+                    config.concurrency,
+                    shutdown.clone(),
+                ));
+                Route::Workers(tx)
+            }
+        };
+        routes.insert(id, route);
+    }
+    for id in targets.keys().filter(|id| !routes.contains_key(*id)) {
+        tracing::warn!("Settings configured for unknown adapter {}", id);
+    }
+
+    for pending in store.load_pending().await? {
+        dispatch(
+            pending.event,
+            &pending.targets,
+            &routes,
+            &store,
+            dead_letters.as_deref(),
+            &shutdown,
+        )
+        .await?;
+    }
 
     loop {
         tokio::select! {
             Some(event) = rx.recv() => {
-                current_log.records.push(event.clone());
-                let mut send_tasks = Vec::new();
+                let mut ids = rules.match_targets(&event);
                 for adapter in &adapters {
-                    if event.channels.contains(&adapter.name()) {
-                        let adapter = adapter.clone();
-                        let event = event.clone();
-                        send_tasks.push(tokio::spawn(async move {
-                            if let Err(e) = adapter.send(&event).await {
-                                tracing::error!("Failed to send event to {}: {}", adapter.name(), e);
-                                Err(e)
-                            } else {
-                                Ok(())
+                    let id = adapter.id();
+                    if event.is_for_channel(&id) && !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+                let ids = store.append(&event, &ids).await?;
+                dispatch(event, &ids, &routes, &store, dead_letters.as_deref(), &shutdown).await?;
+            }
+            letters = next_requeued(dead_letters.as_deref()) => {
+                for letter in letters {
+                    requeue(letter, &routes, dead_letters.as_deref()).await;
+                }
+            }
+            _ = shutdown.cancelled() => {
+                tracing::info!("Shutting down event bus");
+                break;
+            }
+            else => break,
+        }
+    }
+    Ok(())
+}
+
+/// Hands an event over to each of its pending targets.
+///
+/// Handing an event over to the persistent queue of a target counts as a delivery, events handed to
+/// delivery workers are acknowledged by the workers once sent. While a persistent queue is full the
+/// event bus waits for room, leaving the event pending if it shuts down first. An event that cannot
+/// be written to the queue is dead-lettered, the error is returned when there is no dead-letter store.
+async fn dispatch(
+    event: Event,
+    targets: &[String],
+    routes: &HashMap<String, Route>,
+    store: &Arc<dyn EventStorage>,
+    dead_letters: Option<&DeadLetterStore>,
+    shutdown: &CancellationToken,
+) -> Result<(), Error> {
+    let event = Arc::new(event);
+    for target in targets {
+        match routes.get(target) {
+            Some(Route::Queue(queue)) => match queue.push_when_free(&event, shutdown).await {
+                Ok(true) => store.ack(event.id, target).await?,
+                Ok(false) => return Ok(()),
+                Err(e) => {
+                    tracing::error!("Failed to queue event for {}: {}", target, e);
+                    let Some(dead_letters) = dead_letters else {
+                        return Err(e);
+                    };
+                    dead_letters
+                        .put(&DeadLetter::new((*event).clone(), target, 0, &e))
+                        .await?;
+                    store.ack(event.id, target).await?;
+                }
+            },
+            Some(Route::Workers(tx)) => {
+                let delivery = Delivery {
+                    event: event.clone(),
+                    dead_letter: None,
+                };
+                if tx.send(delivery).await.is_err() {
+                    tracing::warn!("Delivery workers of {} have stopped", target);
+                }
+            }
+            None => {
+                tracing::warn!(
+                    "Target {} is no longer configured, discarding event {}",
+                    target,
+                    event.id
+                );
+                store.ack(event.id, target).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Waits for dead letters to be requeued, forever when there is no dead-letter store.
+async fn next_requeued(dead_letters: Option<&DeadLetterStore>) -> Vec<DeadLetter> {
+    match dead_letters {
+        Some(dead_letters) => dead_letters.next_requeued().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Hands a requeued dead letter over to its target again.
+///
+/// The dead letter is removed once the event is delivered, or as soon as it is written to the persistent
+/// queue of the target.
+async fn requeue(
+    letter: DeadLetter,
+    routes: &HashMap<String, Route>,
+    dead_letters: Option<&DeadLetterStore>,
+) {
+    let Some(dead_letters) = dead_letters else {
+        return;
+    };
+    match routes.get(&letter.target) {
+        Some(Route::Queue(queue)) => {
+            let result = match queue.push(&letter.event).await {
+                Ok(()) => dead_letters.remove(letter.id).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to requeue dead letter {}: {}", letter.id, e);
+            }
+        }
+        Some(Route::Workers(tx)) => {
+            let delivery = Delivery {
+                event: Arc::new(letter.event),
+                dead_letter: Some(letter.id),
+            };
+            if tx.send(delivery).await.is_err() {
+                tracing::warn!("Delivery workers of {} have stopped", letter.target);
+            }
+        }
+        None => tracing::warn!(
+            "Target {} of dead letter {} is not configured, leaving it in place",
+            letter.target,
+            letter.id
+        ),
+    }
+}
+
+impl Queue {
+    /// Writes `event` to the persistent queue and wakes up its replayer.
+    async fn push(&self, event: &Event) -> Result<(), Error> {
+        let log = Log {
+            event_name: event.event_name,
+            key: format!("{}/{}", event.s3.bucket.name, event.s3.object.key),
+            records: vec![event.clone()],
+        };
+        self.store.put(&log).await?;
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Writes `event` to the persistent queue, waiting for room while it is full. Returns `false`
+    /// if shutdown is requested before the event could be written.
+    async fn push_when_free(
+        &self,
+        event: &Event,
+        shutdown: &CancellationToken,
+    ) -> Result<bool, Error> {
+        let mut waiting = false;
+        loop {
+            match self.push(event).await {
+                Ok(()) => return Ok(true),
+                Err(Error::QueueFull(dir)) if !waiting => {
+                    tracing::warn!("Queue {} is full, waiting for room", dir);
+                    waiting = true;
+                }
+                Err(Error::QueueFull(_)) => {}
+                Err(e) => return Err(e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(QUEUE_FULL_POLL_INTERVAL) => {}
+                _ = shutdown.cancelled() => return Ok(false),
+            }
+        }
+    }
+}
+
+impl Deliverer {
+    /// Sends events as the retry policy allows, a single event through `send` and several through
+    /// `send_batch`, and returns the outcome with the number of attempts made.
+    ///
+    /// After a partial failure only the events that were not delivered are sent again, a final
+    /// [`Error::PartialBatch`] holds their indices in `events`.
+    async fn send(&self, events: &[Event]) -> (Result<(), Error>, u32) {
+        let adapter = self.adapter.as_ref();
+        // indices of the events left to send, all of them until a partial failure
+        let remaining: Mutex<Option<Vec<usize>>> = Mutex::new(None);
+        let remaining = &remaining;
+        self.retry
+            .run_with_attempts(move || async move {
+                let indices = remaining.lock().expect("remaining events poisoned").clone();
+                let result = match &indices {
+                    None => send_events(adapter, events).await,
+                    Some(indices) => {
+                        let batch: Vec<Event> =
+                            indices.iter().map(|&i| events[i].clone()).collect();
+                        send_events(adapter, &batch).await.map_err(|e| match e {
+                            Error::PartialBatch(failed, e) => {
+                                Error::PartialBatch(failed.iter().map(|&i| indices[i]).collect(), e)
                             }
-                        }));
+                            e => Error::PartialBatch(indices.clone(), Box::new(e)),
+                        })
                     }
+                };
+                if let Err(Error::PartialBatch(failed, _)) = &result {
+                    *remaining.lock().expect("remaining events poisoned") = Some(failed.clone());
                 }
-                for task in send_tasks {
-                    if task.await?.is_err() {
-                        current_log.records.retain(|e| e.id != event.id);
+                result
+            })
+            .await
+    }
+
+    /// Sends a batch of events as the retry policy allows and settles the outcome of each.
+    ///
+    /// A successful delivery is acknowledged in the event storage and removes the dead letter it was
+    /// requeued from. A failed one is recorded as a dead letter, replacing the one it was requeued from,
+    /// and acknowledged; without a dead-letter store it stays pending. Deliveries rejected because the
+    /// target is unavailable are not settled but returned as parked, to be sent again later.
+    async fn deliver(&self, deliveries: Vec<Delivery>) -> Vec<Parked> {
+        let (result, attempts) = match deliveries.as_slice() {
+            [delivery] => self.send(std::slice::from_ref(&*delivery.event)).await,
+            deliveries => {
+                let events: Vec<Event> = deliveries.iter().map(|d| (*d.event).clone()).collect();
+                self.send(&events).await
+            }
+        };
+        // indices of the failed deliveries, all of them unless the batch failed partially
+        let (failed, error) = match &result {
+            Ok(()) => (None, None),
+            Err(Error::PartialBatch(failed, e)) => (Some(failed.as_slice()), Some(e.as_ref())),
+            Err(e) => (None, Some(e)),
+        };
+        let mut parked = Vec::new();
+        for (i, delivery) in deliveries.into_iter().enumerate() {
+            match error {
+                Some(e) if failed.is_none_or(|failed| failed.contains(&i)) => {
+                    if e.is_unavailable() {
+                        parked.push(Parked {
+                            event: delivery.event.id,
+                            dead_letter: delivery.dead_letter,
+                        });
+                    } else {
+                        self.settle(delivery, Err(e), attempts).await;
                     }
                 }
-                if !current_log.records.is_empty() {
-                    pending_logs.push(current_log.clone());
+                _ => self.settle(delivery, Ok(()), attempts).await,
+            }
+        }
+        if let Some(e) = error
+            && !parked.is_empty()
+        {
+            tracing::debug!("Parking {} events: {}", parked.len(), e);
+        }
+        parked
+    }
+
+    /// Reloads the parked deliveries still pending, from the dead-letter store for those requeued from
+    /// a dead letter and from the event storage for the others.
+    async fn unpark(&self, parked: Vec<Parked>) -> Result<Vec<Delivery>, Error> {
+        let target = self.adapter.id();
+        let mut pending: HashMap<Uuid, Event> = HashMap::new();
+        if parked.iter().any(|p| p.dead_letter.is_none()) {
+            pending = self
+                .store
+                .load_pending()
+                .await?
+                .into_iter()
+                .filter(|p| p.targets.contains(&target))
+                .map(|p| (p.event.id, p.event))
+                .collect();
+        }
+        let mut deliveries = Vec::with_capacity(parked.len());
+        for Parked { event, dead_letter } in parked {
+            let event = match (dead_letter, &self.dead_letters) {
+                (Some(id), Some(dead_letters)) => dead_letters.get(id).await?.map(|l| l.event),
+                _ => pending.remove(&event),
+            };
+            // settled in the meantime, by a purge or a removed dead letter
+            if let Some(event) = event {
+                deliveries.push(Delivery {
+                    event: Arc::new(event),
+                    dead_letter,
+                });
+            }
+        }
+        Ok(deliveries)
+    }
+
+    async fn settle(&self, delivery: Delivery, result: Result<(), &Error>, attempts: u32) {
+        let Delivery { event, dead_letter } = delivery;
+        let target = self.adapter.id();
+        let settled = match (result, &self.dead_letters) {
+            (Ok(()), dead_letters) => {
+                if let (Some(id), Some(dead_letters)) = (dead_letter, dead_letters)
+                    && let Err(e) = dead_letters.remove(id).await
+                {
+                    tracing::error!("Failed to remove dead letter {}: {}", id, e);
                 }
-                current_log.records.clear();
+                self.store.ack(event.id, &target).await
             }
-            _ = shutdown.cancelled() => {
-                tracing::info!("Shutting down event bus, saving pending logs...");
-                if !current_log.records.is_empty() {
-                    pending_logs.push(current_log);
+            (Err(e), Some(dead_letters)) => {
+                tracing::error!("Failed to send event to {}: {}", target, e);
+                let mut letter = DeadLetter::new((*event).clone(), &target, attempts, e);
+                if let Some(id) = dead_letter {
+                    letter.id = id;
                 }
-                store.save_logs(&pending_logs).await?;
-                break;
+                match dead_letters.put(&letter).await {
+                    Ok(()) => self.store.ack(event.id, &target).await,
+                    Err(e) => Err(e),
+                }
+            }
+            (Err(e), None) => {
+                tracing::error!("Failed to send event to {}: {}", target, e);
+                Ok(())
+            }
+        };
+        if let Err(e) = settled {
+            tracing::error!("Failed to settle delivery of event {}: {}", event.id, e);
+        }
+    }
+}
+
+/// Sends a single event through `send` and several through `send_batch`.
+async fn send_events(adapter: &dyn ChannelAdapter, events: &[Event]) -> Result<(), Error> {
+    match events {
+        [event] => adapter.send(event).await,
+        events => adapter.send_batch(events).await,
+    }
+}
+
+/// Approximates the size of an event on the wire by the size of its native JSON form.
+fn event_size(event: &Event) -> usize {
+    serde_json::to_vec(event).map_or(0, |data| data.len())
+}
+
+/// Adds the deliveries arriving within the linger time of `batch` to it until it is full.
+async fn fill_batch(
+    rx: &mut mpsc::Receiver<Delivery>,
+    batch: &mut Vec<Delivery>,
+    config: &BatchConfig,
+) {
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(config.linger_ms);
+    let mut bytes: usize = batch.iter().map(|d| event_size(&d.event)).sum();
+    while batch.len() < config.max_events && bytes < config.max_bytes {
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(delivery)) => {
+                bytes += event_size(&delivery.event);
+                batch.push(delivery);
             }
+            _ => break,
+        }
+    }
+}
+
+/// Delivers the events of an adapter with at most `concurrency` deliveries in flight, in batches
+/// when the adapter has batching enabled.
+///
+/// Deliveries rejected because the adapter is unavailable are parked and sent again every
+/// [`QUEUE_RETRY_INTERVAL`]. Parked events are left to the event storage, so the workers keep taking
+/// new deliveries however long the adapter stays unavailable.
+async fn run_workers(
+    deliverer: Deliverer,
+    mut rx: mpsc::Receiver<Delivery>,
+    concurrency: usize,
+    shutdown: CancellationToken,
+) {
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let parked: Arc<Mutex<Vec<Parked>>> = Arc::new(Mutex::new(Vec::new()));
+    let mut retry = tokio::time::interval(QUEUE_RETRY_INTERVAL);
+    retry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let batches = tokio::select! {
+            Some(delivery) = rx.recv() => {
+                let mut batch = vec![delivery];
+                if let Some(config) = &deliverer.batch {
+                    fill_batch(&mut rx, &mut batch, config).await;
+                }
+                vec![batch]
+            }
+            _ = retry.tick() => {
+                let unparked = std::mem::take(&mut *parked.lock().expect("parked deliveries poisoned"));
+                if unparked.is_empty() {
+                    continue;
+                }
+                let deliveries = match deliverer.unpark(unparked).await {
+                    Ok(deliveries) => deliveries,
+                    Err(e) => {
+                        // left pending, they are delivered on the next start
+                        tracing::error!(
+                            "Failed to reload parked events of {}: {}",
+                            deliverer.adapter.id(),
+                            e
+                        );
+                        continue;
+                    }
+                };
+                let size = deliverer.batch.as_ref().map_or(1, |batch| batch.max_events);
+                let mut batches = Vec::new();
+                let mut deliveries = deliveries.into_iter().peekable();
+                while deliveries.peek().is_some() {
+                    batches.push(deliveries.by_ref().take(size).collect());
+                }
+                batches
+            }
+            _ = shutdown.cancelled() => break,
             else => break,
+        };
+        for batch in batches {
+            let Ok(permit) = permits.clone().acquire_owned().await else {
+                return;
+            };
+            let deliverer = deliverer.clone();
+            let parked = parked.clone();
+            tokio::spawn(async move {
+                let rejected = deliverer.deliver(batch).await;
+                if !rejected.is_empty() {
+                    parked
+                        .lock()
+                        .expect("parked deliveries poisoned")
+                        .extend(rejected);
+                }
+                drop(permit);
+            });
         }
     }
+}
+
+/// Drains the persistent queue of an adapter, oldest entry first.
+///
+/// Draining stops at the first failed delivery and resumes when new entries are queued or after
+/// [`QUEUE_RETRY_INTERVAL`], so entries are delivered in order once the adapter is reachable again.
+/// With batching enabled consecutive entries are sent together, up to the batch size limits.
+/// Entries failing with an error the retry policy does not retry are moved to the dead-letter store
+/// instead, so they cannot block the queue; an open circuit or a failed health check pauses
+/// draining like any other failure.
+async fn replay_queue(
+    deliverer: Deliverer,
+    queue: Arc<QueueStore>,
+    notify: Arc<Notify>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        if let Err(e) = drain_queue(&deliverer, &queue).await {
+            tracing::warn!(
+                "Delivery from queue of {} paused: {}",
+                deliverer.adapter.id(),
+                e
+            );
+        }
+        tokio::select! {
+            _ = notify.notified() => {}
+            _ = tokio::time::sleep(QUEUE_RETRY_INTERVAL), if !queue.is_empty() => {}
+            _ = shutdown.cancelled() => break,
+        }
+    }
+}
+
+async fn drain_queue(deliverer: &Deliverer, queue: &QueueStore) -> Result<(), Error> {
+    let (max_events, max_bytes) = deliverer
+        .batch
+        .as_ref()
+        .map_or((1, usize::MAX), |batch| (batch.max_events, batch.max_bytes));
+    let mut keys = Vec::new();
+    let mut counts = Vec::new();
+    let mut events = Vec::new();
+    let mut bytes = 0;
+    for key in queue.list().await? {
+        let log = match queue.get(&key).await {
+            Ok(log) => log,
+            Err(Error::Serde(e)) => {
+                tracing::error!("Discarding corrupt queue entry {}: {}", key, e);
+                queue.del(&key).await?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if deliverer.batch.is_some() {
+            bytes += log.records.iter().map(event_size).sum::<usize>();
+        }
+        counts.push(log.records.len());
+        events.extend(log.records);
+        keys.push(key);
+        if events.len() >= max_events || bytes >= max_bytes {
+            send_queued(deliverer, queue, &keys, &counts, &events).await?;
+            keys.clear();
+            counts.clear();
+            events.clear();
+            bytes = 0;
+        }
+    }
+    if !keys.is_empty() {
+        send_queued(deliverer, queue, &keys, &counts, &events).await?;
+    }
     Ok(())
 }
+
+/// Sends the events of the queue entries `keys`, holding `counts` events each, and removes the
+/// entries once settled.
+///
+/// After a partial failure the entries whose events were all delivered are removed, so only the
+/// failed events are dead-lettered, or sent again on the next drain.
+async fn send_queued(
+    deliverer: &Deliverer,
+    queue: &QueueStore,
+    keys: &[String],
+    counts: &[usize],
+    events: &[Event],
+) -> Result<(), Error> {
+    let target = deliverer.adapter.id();
+    let (result, attempts) = deliverer.send(events).await;
+    let (failed, e) = match result {
+        Ok(()) => (Vec::new(), None),
+        Err(Error::PartialBatch(failed, e)) => (failed, Some(*e)),
+        Err(e) => ((0..events.len()).collect(), Some(e)),
+    };
+    let result = match (e, &deliverer.dead_letters) {
+        (None, _) => Ok(()),
+        (Some(e), _) if e.is_unavailable() => Err(e),
+        (Some(e), Some(dead_letters)) if !deliverer.retry.should_retry(&e) => {
+            tracing::error!("Failed to send queued events to {}: {}", target, e);
+            for &i in &failed {
+                let letter = DeadLetter::new(events[i].clone(), &target, attempts, &e);
+                dead_letters.put(&letter).await?;
+            }
+            for key in keys {
+                queue.del(key).await?;
+            }
+            return Ok(());
+        }
+        (Some(e), _) => Err(e),
+    };
+    let mut first = 0;
+    for (key, &count) in keys.iter().zip(counts) {
+        let entry = first..first + count;
+        first += count;
+        if !failed.iter().any(|i| entry.contains(i)) {
+            queue.del(key).await?;
+        }
+    }
+    result
+}
@@ -0,0 +1,307 @@
+use crate::Error;
+use crate::QueueStore;
+use crate::{Event, Log};
+use crate::{OverflowConfig, OverflowPolicy};
+use crate::{Subscription, SubscriptionFilter};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, broadcast, mpsc};
+
+/// Interval at which reading the spill directory is retried after a failure.
+const SPILL_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events waiting for the event bus.
+struct Buffer {
+    events: VecDeque<Event>,
+    /// Number of spilled events not fed back yet, new events are spilled while it is not 0 so that
+    /// they stay behind the spilled ones
+    spilled: u64,
+    closed: bool,
+}
+
+struct Shared {
+    buffer: Mutex<Buffer>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    timeout: Duration,
+    spill: Option<QueueStore>,
+    /// signalled when events are buffered or spilled
+    filled: Notify,
+    /// signalled when room is made in the buffer
+    drained: Notify,
+    dropped: AtomicU64,
+    subscribers: broadcast::Sender<Arc<Event>>,
+}
+
+/// Outcome of buffering an event without waiting.
+enum Push {
+    Done,
+    Full(Event),
+    Spill(Event),
+    Closed(Event),
+}
+
+/// The sending side of the event channel of a notification system.
+///
+/// Holds up to `channel_capacity` events waiting for the event bus and applies the configured
+/// [`OverflowPolicy`] when it is full. Cloning the sender is cheap, every clone feeds the same
+/// channel.
+#[derive(Clone)]
+pub struct EventSender {
+    shared: Arc<Shared>,
+}
+
+impl EventSender {
+    /// Creates the event channel, returns its sender and the receiver to hand to the event bus.
+    ///
+    /// Events spilled by a previous run are fed into the channel first.
+    pub(crate) async fn new(
+        capacity: usize,
+        subscriber_capacity: usize,
+        config: &OverflowConfig,
+        store_path: &str,
+    ) -> Result<(Self, mpsc::Receiver<Event>), Error> {
+        if capacity == 0 {
+            return Err(Error::ConfigError(
+                "channel_capacity must be greater than 0".to_string(),
+            ));
+        }
+        if subscriber_capacity == 0 {
+            return Err(Error::ConfigError(
+                "subscriber_capacity must be greater than 0".to_string(),
+            ));
+        }
+        let spill = match config.policy {
+            OverflowPolicy::Spill => {
+                let dir = config
+                    .spill_dir
+                    .clone()
+                    .unwrap_or_else(|| format!("{store_path}/spill"));
+                Some(QueueStore::new(&dir, config.spill_limit).await?)
+            }
+            _ => None,
+        };
+        let shared = Arc::new(Shared {
+            buffer: Mutex::new(Buffer {
+                events: VecDeque::with_capacity(capacity),
+                spilled: spill.as_ref().map_or(0, QueueStore::len),
+                closed: false,
+            }),
+            capacity,
+            policy: config.policy,
+            timeout: Duration::from_millis(config.timeout_ms),
+            spill,
+            filled: Notify::new(),
+            drained: Notify::new(),
+            dropped: AtomicU64::new(0),
+            subscribers: broadcast::channel(subscriber_capacity).0,
+        });
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(forward(shared.clone(), tx));
+        Ok((Self { shared }, rx))
+    }
+
+    /// Sends an event, applying the overflow policy when the channel is full.
+    ///
+    /// Fails with [`Error::ChannelFull`] when the `timeout` policy gave up waiting.
+    pub async fn send(&self, event: Event) -> Result<(), Error> {
+        let deadline = tokio::time::Instant::now() + self.shared.timeout;
+        let mut event = event;
+        loop {
+            let drained = self.shared.drained.notified();
+            match self.shared.push(event, self.shared.policy) {
+                Push::Done => return Ok(()),
+                Push::Spill(event) => return self.shared.spill(event).await,
+                Push::Closed(event) => {
+                    return Err(Error::ChannelSend(Box::new(mpsc::error::SendError(event))));
+                }
+                Push::Full(full) => event = full,
+            }
+            if self.shared.policy == OverflowPolicy::Timeout {
+                if tokio::time::timeout_at(deadline, drained).await.is_err() {
+                    return Err(Error::ChannelFull(Box::new(event)));
+                }
+            } else {
+                drained.await;
+            }
+        }
+    }
+
+    /// Sends an event without waiting, failing with [`Error::ChannelFull`] when the channel is full
+    /// whatever the overflow policy.
+    pub fn try_send(&self, event: Event) -> Result<(), Error> {
+        match self.shared.push(event, OverflowPolicy::Block) {
+            Push::Done => Ok(()),
+            Push::Full(event) | Push::Spill(event) => Err(Error::ChannelFull(Box::new(event))),
+            Push::Closed(event) => Err(Error::ChannelSend(Box::new(mpsc::error::SendError(event)))),
+        }
+    }
+
+    /// Returns the number of events waiting in memory for the event bus.
+    pub fn len(&self) -> usize {
+        self.shared.lock().events.len()
+    }
+
+    /// Returns `true` if no event is waiting in memory for the event bus.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Subscribes to the events handed to the event bus that pass `filter`.
+    pub fn subscribe(&self, filter: SubscriptionFilter) -> Subscription {
+        Subscription::new(self.shared.subscribers.subscribe(), filter)
+    }
+
+    /// Returns the number of events discarded by the `drop_newest` and `drop_oldest` policies.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::SeqCst)
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buffer> {
+        self.buffer.lock().expect("event channel poisoned")
+    }
+
+    /// Buffers `event` if there is room, otherwise applies the policies that need no waiting.
+    fn push(&self, event: Event, policy: OverflowPolicy) -> Push {
+        let mut buffer = self.lock();
+        if buffer.closed {
+            return Push::Closed(event);
+        }
+        if buffer.spilled > 0 {
+            if policy == OverflowPolicy::Spill {
+                buffer.spilled += 1;
+                return Push::Spill(event);
+            }
+            return Push::Full(event);
+        }
+        if buffer.events.len() < self.capacity {
+            buffer.events.push_back(event);
+            drop(buffer);
+            self.filled.notify_one();
+            return Push::Done;
+        }
+        match policy {
+            OverflowPolicy::Block | OverflowPolicy::Timeout => Push::Full(event),
+            OverflowPolicy::DropNewest => {
+                drop(buffer);
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+                tracing::warn!("Event channel full, dropping event {}", event.id);
+                Push::Done
+            }
+            OverflowPolicy::DropOldest => {
+                let oldest = buffer.events.pop_front();
+                buffer.events.push_back(event);
+                drop(buffer);
+                if let Some(oldest) = oldest {
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                    tracing::warn!("Event channel full, dropping event {}", oldest.id);
+                }
+                Push::Done
+            }
+            OverflowPolicy::Spill => {
+                buffer.spilled += 1;
+                Push::Spill(event)
+            }
+        }
+    }
+
+    /// Writes `event` to the spill directory, counted in `spilled` beforehand.
+    async fn spill(&self, event: Event) -> Result<(), Error> {
+        let log = Log {
+            event_name: event.event_name,
+            key: format!("{}/{}", event.s3.bucket.name, event.s3.object.key),
+            records: vec![event],
+        };
+        let result = match &self.spill {
+            Some(spill) => spill.put(&log).await.map(|_| ()),
+            None => Err(Error::custom("spill directory not opened")),
+        };
+        if result.is_err() {
+            self.lock().spilled -= 1;
+        }
+        self.filled.notify_one();
+        result
+    }
+
+    /// Broadcasts an event handed to the event bus to the subscriptions.
+    fn publish(&self, event: &Event) {
+        if self.subscribers.receiver_count() > 0 {
+            let _ = self.subscribers.send(Arc::new(event.clone()));
+        }
+    }
+
+    /// Takes the oldest buffered event.
+    fn pop(&self) -> Option<Event> {
+        let event = self.lock().events.pop_front();
+        if event.is_some() {
+            self.drained.notify_waiters();
+        }
+        event
+    }
+
+    /// Feeds the spilled events into the event bus, oldest first. Returns whether any was found.
+    async fn unspill(&self, tx: &mpsc::Sender<Event>) -> Result<bool, Error> {
+        let Some(spill) = &self.spill else {
+            return Ok(false);
+        };
+        let keys = spill.list().await?;
+        for key in &keys {
+            match spill.get(key).await {
+                Ok(log) => {
+                    for event in log.records {
+                        self.publish(&event);
+                        if tx.send(event).await.is_err() {
+                            return Ok(false);
+                        }
+                    }
+                }
+                Err(Error::Serde(e)) => {
+                    tracing::error!("Discarding corrupt spilled entry {}: {}", key, e);
+                }
+                Err(e) => return Err(e),
+            }
+            spill.del(key).await?;
+            self.lock().spilled -= 1;
+        }
+        Ok(!keys.is_empty())
+    }
+}
+
+/// Moves events from the channel to the event bus, buffered events first as they are older than
+/// the spilled ones.
+async fn forward(shared: Arc<Shared>, tx: mpsc::Sender<Event>) {
+    loop {
+        let filled = shared.filled.notified();
+        if let Some(event) = shared.pop() {
+            shared.publish(&event);
+            if tx.send(event).await.is_err() {
+                break;
+            }
+            continue;
+        }
+        if shared.lock().spilled > 0 {
+            match shared.unspill(&tx).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!("Failed to read spilled events: {}", e);
+                    tokio::time::sleep(SPILL_RETRY_INTERVAL).await;
+                    continue;
+                }
+            }
+        }
+        if tx.is_closed() {
+            break;
+        }
+        tokio::select! {
+            _ = filled => {}
+            _ = tx.closed() => break,
+        }
+    }
+    shared.lock().closed = true;
+    shared.drained.notify_waiters();
+}
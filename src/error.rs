@@ -20,10 +20,55 @@ pub enum Error {
     #[cfg(feature = "mqtt")]
     #[error("MQTT error: {0}")]
     Mqtt(#[from] Box<rumqttc::ClientError>),
+    #[cfg(feature = "nats")]
+    #[error("NATS error: {0}")]
+    Nats(async_nats::Error),
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {0}")]
+    Redis(#[from] Box<redis::RedisError>),
+    #[cfg(feature = "amqp")]
+    #[error("AMQP error: {0}")]
+    Amqp(#[from] Box<lapin::Error>),
+    #[cfg(feature = "amqp")]
+    #[error("AMQP message not confirmed: {0}")]
+    AmqpUnconfirmed(String),
+    #[cfg(feature = "postgres")]
+    #[error("PostgreSQL error: {0}")]
+    Postgres(#[from] Box<tokio_postgres::Error>),
+    #[cfg(feature = "postgres")]
+    #[error("PostgreSQL pool error: {0}")]
+    PostgresPool(#[from] Box<deadpool_postgres::PoolError>),
+    #[cfg(feature = "mysql")]
+    #[error("MySQL error: {0}")]
+    Mysql(#[from] Box<mysql_async::Error>),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] Box<rusqlite::Error>),
     #[error("Channel send error: {0}")]
     ChannelSend(#[from] Box<error::SendError<crate::event::Event>>),
     #[error("Feature disabled: {0}")]
     FeatureDisabled(&'static str),
+    #[error("Event channel is full")]
+    ChannelFull(Box<crate::event::Event>),
+    #[error("Queue store is full: {0}")]
+    QueueFull(String),
+    /// Only part of a batch was delivered, holding the indices of the events that were not
+    #[error("{count} events of the batch were not delivered: {1}", count = .0.len())]
+    PartialBatch(Vec<usize>, Box<Error>),
+    #[error("Circuit open for target {0}")]
+    CircuitOpen(String),
+    #[error("Target {0} failed its health check")]
+    TargetInactive(String),
+    #[error("Dead letter not found: {0}")]
+    DeadLetterNotFound(uuid::Uuid),
+    #[error("Unknown event name: {0}")]
+    UnknownEventName(String),
+    #[error("Unknown target ARN: {0}")]
+    UnknownTarget(String),
+    #[error("Invalid filter rule: {0}")]
+    InvalidFilterRule(String),
+    #[error("XML error: {0}")]
+    Xml(String),
     #[error("Event bus already started")]
     EventBusStarted,
     #[error("necessary fields are missing:{0}")]
@@ -70,6 +115,79 @@ impl From<rumqttc::ClientError> for Error {
     }
 }
 
+#[cfg(feature = "nats")]
+impl From<async_nats::ConnectError> for Error {
+    fn from(err: async_nats::ConnectError) -> Self {
+        Error::Nats(Box::new(err))
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<async_nats::client::PublishError> for Error {
+    fn from(err: async_nats::client::PublishError) -> Self {
+        Error::Nats(Box::new(err))
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<async_nats::client::FlushError> for Error {
+    fn from(err: async_nats::client::FlushError) -> Self {
+        Error::Nats(Box::new(err))
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<async_nats::jetstream::context::PublishError> for Error {
+    fn from(err: async_nats::jetstream::context::PublishError) -> Self {
+        Error::Nats(Box::new(err))
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for Error {
+    fn from(err: redis::RedisError) -> Self {
+        Error::Redis(Box::new(err))
+    }
+}
+
+#[cfg(feature = "amqp")]
+impl From<lapin::Error> for Error {
+    fn from(err: lapin::Error) -> Self {
+        Error::Amqp(Box::new(err))
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Error> for Error {
+    fn from(err: tokio_postgres::Error) -> Self {
+        Error::Postgres(Box::new(err))
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<deadpool_postgres::PoolError> for Error {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        match err {
+            deadpool_postgres::PoolError::Backend(err) => Error::Postgres(Box::new(err)),
+            err => Error::PostgresPool(Box::new(err)),
+        }
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl From<mysql_async::Error> for Error {
+    fn from(err: mysql_async::Error) -> Self {
+        Error::Mysql(Box::new(err))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(Box::new(err))
+    }
+}
+
 impl From<figment::Error> for Error {
     fn from(err: figment::Error) -> Self {
         Error::Figment(Box::new(err))
@@ -77,6 +195,57 @@ impl From<figment::Error> for Error {
 }
 
 impl Error {
+    /// Determine whether the failure is transient, so the operation may succeed when retried.
+    ///
+    /// Connection failures, timeouts, HTTP 408/429/5xx responses and broker errors are transient;
+    /// invalid configuration, serialization failures and other HTTP 4xx responses are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Io(_) | Error::QueueFull(_) => true,
+            Error::PartialBatch(_, e) => e.is_retryable(),
+            Error::Http(e) => match e.status() {
+                Some(status) => {
+                    status.is_server_error()
+                        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                None => !e.is_builder(),
+            },
+            #[cfg(feature = "kafka")]
+            Error::Kafka(_) => true,
+            #[cfg(feature = "mqtt")]
+            Error::Mqtt(_) => true,
+            #[cfg(feature = "nats")]
+            Error::Nats(_) => true,
+            #[cfg(feature = "redis")]
+            Error::Redis(e) => !matches!(e.retry_method(), redis::RetryMethod::NoRetry),
+            #[cfg(feature = "amqp")]
+            Error::Amqp(_) | Error::AmqpUnconfirmed(_) => true,
+            // connection failures and the connection exception, transaction rollback, insufficient
+            // resources and operator intervention SQLSTATE classes
+            #[cfg(feature = "postgres")]
+            Error::Postgres(e) => e
+                .as_db_error()
+                .is_none_or(|e| matches!(&e.code().code()[..2], "08" | "40" | "53" | "57")),
+            #[cfg(feature = "postgres")]
+            Error::PostgresPool(e) => matches!(**e, deadpool_postgres::PoolError::Timeout(_)),
+            // connection failures, too many connections, lock wait timeouts and deadlocks
+            #[cfg(feature = "mysql")]
+            Error::Mysql(e) => match &**e {
+                mysql_async::Error::Io(_) => true,
+                mysql_async::Error::Server(e) => matches!(e.code, 1040 | 1205 | 1213),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Determine whether the send was rejected without reaching the target, because the target is
+    /// known to be unreachable: its circuit is open or it failed its health check.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Error::CircuitOpen(_) | Error::TargetInactive(_))
+    }
+
     pub(crate) fn custom(msg: &str) -> Error {
         Self::Custom(msg.to_string())
     }
@@ -1,10 +1,11 @@
 use crate::Error;
+use crate::PayloadFormat;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use serde_with::{DeserializeFromStr, SerializeDisplay};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::{SmallVec, smallvec};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::{BitAnd, BitOr};
 use strum::{Display, EnumString};
 use uuid::Uuid;
 
@@ -298,14 +299,120 @@ impl Event {
 
     /// Determine whether an event belongs to a specific type
     pub fn is_type(&self, event_type: Name) -> bool {
-        let mask = event_type.mask();
-        (self.event_name.mask() & mask) != 0
+        NameSet::from(event_type).contains(self.event_name)
+    }
+
+    /// Returns the time the event occurred, `event_time` if it is an RFC 3339 time and the time the
+    /// event was created otherwise.
+    pub fn occurred_at(&self) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&self.event_time)
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or(self.timestamp)
     }
 
     /// Determine whether an event needs to be sent to a specific channel
     pub fn is_for_channel(&self, channel: &str) -> bool {
         self.channels.iter().any(|c| c == channel)
     }
+
+    /// Converts the event into an S3 notification record
+    ///
+    /// The object key is URL encoded and `x-amz-request-id` is derived from the event id when the
+    /// response elements do not carry one.
+    pub fn to_s3_record(&self) -> S3Record {
+        let mut response_elements = self.response_elements.clone();
+        response_elements
+            .entry(AMZ_REQUEST_ID.to_string())
+            .or_insert_with(|| self.id.simple().to_string().to_uppercase());
+        let mut s3 = self.s3.clone();
+        s3.object.key = form_urlencoded::byte_serialize(s3.object.key.as_bytes()).collect();
+        S3Record {
+            event_version: self.event_version.clone(),
+            event_source: self.event_source.clone(),
+            aws_region: self.aws_region.clone(),
+            event_time: self.event_time.clone(),
+            event_name: self.event_name.as_s3_str().to_string(),
+            user_identity: self.user_identity.clone(),
+            request_parameters: self.request_parameters.clone(),
+            response_elements,
+            s3,
+        }
+    }
+
+    /// Serializes the event in its native form, with the event name in the given form
+    fn to_native(&self, names: NameFormat) -> Result<serde_json::Value, Error> {
+        let mut native = serde_json::to_value(self)?;
+        if names == NameFormat::S3 {
+            native["eventName"] = self.event_name.as_s3_str().into();
+        }
+        Ok(native)
+    }
+
+    /// Serializes the event in the given wire format, `names` selecting the form of the event name
+    /// in native payloads
+    pub fn to_payload(&self, format: PayloadFormat, names: NameFormat) -> Result<String, Error> {
+        let payload = match format {
+            PayloadFormat::Native => serde_json::to_string(&self.to_native(names)?)?,
+            PayloadFormat::S3 => serde_json::to_string(&S3Notification {
+                records: vec![self.to_s3_record()],
+            })?,
+        };
+        Ok(payload)
+    }
+
+    /// Serializes several events in the given wire format, as a JSON array or a single S3
+    /// `Records` envelope
+    pub fn to_batch_payload(
+        events: &[Event],
+        format: PayloadFormat,
+        names: NameFormat,
+    ) -> Result<String, Error> {
+        let payload = match format {
+            PayloadFormat::Native => serde_json::to_string(
+                &events
+                    .iter()
+                    .map(|event| event.to_native(names))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )?,
+            PayloadFormat::S3 => serde_json::to_string(&S3Notification {
+                records: events.iter().map(Event::to_s3_record).collect(),
+            })?,
+        };
+        Ok(payload)
+    }
+}
+
+/// Response element holding the id of the request that caused an event.
+const AMZ_REQUEST_ID: &str = "x-amz-request-id";
+
+/// An event in the S3 notification wire format.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct S3Record {
+    #[serde(rename = "eventVersion")]
+    pub event_version: String,
+    #[serde(rename = "eventSource")]
+    pub event_source: String,
+    #[serde(rename = "awsRegion")]
+    pub aws_region: String,
+    #[serde(rename = "eventTime")]
+    pub event_time: String,
+    /// S3 event type such as `s3:ObjectCreated:Put`
+    #[serde(rename = "eventName")]
+    pub event_name: String,
+    #[serde(rename = "userIdentity")]
+    pub user_identity: Identity,
+    #[serde(rename = "requestParameters")]
+    pub request_parameters: HashMap<String, String>,
+    #[serde(rename = "responseElements")]
+    pub response_elements: HashMap<String, String>,
+    pub s3: Metadata,
+}
+
+/// The envelope S3 delivers notifications in, `{"Records":[...]}`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct S3Notification {
+    #[serde(rename = "Records")]
+    pub records: Vec<S3Record>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -316,54 +423,162 @@ pub struct Log {
     pub records: Vec<Event>,
 }
 
-#[derive(
-    Debug, Clone, Copy, PartialEq, Eq, SerializeDisplay, DeserializeFromStr, Display, EnumString,
-)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u32)]
 pub enum Name {
-    ObjectAccessedGet,
-    ObjectAccessedGetRetention,
-    ObjectAccessedGetLegalHold,
-    ObjectAccessedHead,
-    ObjectAccessedAttributes,
-    ObjectCreatedCompleteMultipartUpload,
-    ObjectCreatedCopy,
-    ObjectCreatedPost,
-    ObjectCreatedPut,
-    ObjectCreatedPutRetention,
-    ObjectCreatedPutLegalHold,
-    ObjectCreatedPutTagging,
-    ObjectCreatedDeleteTagging,
-    ObjectRemovedDelete,
-    ObjectRemovedDeleteMarkerCreated,
-    ObjectRemovedDeleteAllVersions,
-    ObjectRemovedNoOp,
-    BucketCreated,
-    BucketRemoved,
-    ObjectReplicationFailed,
-    ObjectReplicationComplete,
-    ObjectReplicationMissedThreshold,
-    ObjectReplicationReplicatedAfterThreshold,
-    ObjectReplicationNotTracked,
-    ObjectRestorePost,
-    ObjectRestoreCompleted,
-    ObjectTransitionFailed,
-    ObjectTransitionComplete,
-    ObjectManyVersions,
-    ObjectLargeVersions,
-    PrefixManyFolders,
-    IlmDelMarkerExpirationDelete,
-    ObjectAccessedAll,
-    ObjectCreatedAll,
-    ObjectRemovedAll,
-    ObjectReplicationAll,
-    ObjectRestoreAll,
-    ObjectTransitionAll,
-    ObjectScannerAll,
-    Everything,
+    ObjectAccessedGet = 1,
+    ObjectAccessedGetRetention = 2,
+    ObjectAccessedGetLegalHold = 3,
+    ObjectAccessedHead = 4,
+    ObjectAccessedAttributes = 5,
+    ObjectCreatedCompleteMultipartUpload = 6,
+    ObjectCreatedCopy = 7,
+    ObjectCreatedPost = 8,
+    ObjectCreatedPut = 9,
+    ObjectCreatedPutRetention = 10,
+    ObjectCreatedPutLegalHold = 11,
+    ObjectCreatedPutTagging = 12,
+    ObjectCreatedDeleteTagging = 13,
+    ObjectRemovedDelete = 14,
+    ObjectRemovedDeleteMarkerCreated = 15,
+    ObjectRemovedDeleteAllVersions = 16,
+    ObjectRemovedNoOp = 17,
+    BucketCreated = 18,
+    BucketRemoved = 19,
+    ObjectReplicationFailed = 20,
+    ObjectReplicationComplete = 21,
+    ObjectReplicationMissedThreshold = 22,
+    ObjectReplicationReplicatedAfterThreshold = 23,
+    ObjectReplicationNotTracked = 24,
+    ObjectRestorePost = 25,
+    ObjectRestoreCompleted = 26,
+    ObjectTransitionFailed = 27,
+    ObjectTransitionComplete = 28,
+    ObjectManyVersions = 29,
+    ObjectLargeVersions = 30,
+    PrefixManyFolders = 31,
+    IlmDelMarkerExpirationDelete = 32,
+    ObjectAccessedAll = 33,
+    ObjectCreatedAll = 34,
+    ObjectRemovedAll = 35,
+    ObjectReplicationAll = 36,
+    ObjectRestoreAll = 37,
+    ObjectTransitionAll = 38,
+    ObjectScannerAll = 39,
+    Everything = 40,
 }
 
+/// Form event names take in native payloads, S3 payloads always carry the S3 form.
+///
+/// `Name` itself always serializes in the constant form and deserializes from both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameFormat {
+    /// the variant name in screaming snake case, e.g. `OBJECT_CREATED_PUT`
+    #[default]
+    Constant,
+    /// the canonical S3 event type, e.g. `s3:ObjectCreated:Put`
+    S3,
+}
+
+/// Canonical S3 event type string of every `Name`.
+const S3_NAMES: [(Name, &str); 40] = [
+    (Name::ObjectAccessedGet, "s3:ObjectAccessed:Get"),
+    (
+        Name::ObjectAccessedGetRetention,
+        "s3:ObjectAccessed:GetRetention",
+    ),
+    (
+        Name::ObjectAccessedGetLegalHold,
+        "s3:ObjectAccessed:GetLegalHold",
+    ),
+    (Name::ObjectAccessedHead, "s3:ObjectAccessed:Head"),
+    (
+        Name::ObjectAccessedAttributes,
+        "s3:ObjectAccessed:Attributes",
+    ),
+    (
+        Name::ObjectCreatedCompleteMultipartUpload,
+        "s3:ObjectCreated:CompleteMultipartUpload",
+    ),
+    (Name::ObjectCreatedCopy, "s3:ObjectCreated:Copy"),
+    (Name::ObjectCreatedPost, "s3:ObjectCreated:Post"),
+    (Name::ObjectCreatedPut, "s3:ObjectCreated:Put"),
+    (
+        Name::ObjectCreatedPutRetention,
+        "s3:ObjectCreated:PutRetention",
+    ),
+    (
+        Name::ObjectCreatedPutLegalHold,
+        "s3:ObjectCreated:PutLegalHold",
+    ),
+    (Name::ObjectCreatedPutTagging, "s3:ObjectCreated:PutTagging"),
+    (
+        Name::ObjectCreatedDeleteTagging,
+        "s3:ObjectCreated:DeleteTagging",
+    ),
+    (Name::ObjectRemovedDelete, "s3:ObjectRemoved:Delete"),
+    (
+        Name::ObjectRemovedDeleteMarkerCreated,
+        "s3:ObjectRemoved:DeleteMarkerCreated",
+    ),
+    (
+        Name::ObjectRemovedDeleteAllVersions,
+        "s3:ObjectRemoved:DeleteAllVersions",
+    ),
+    (Name::ObjectRemovedNoOp, "s3:ObjectRemoved:NoOP"),
+    (Name::BucketCreated, "s3:BucketCreated:*"),
+    (Name::BucketRemoved, "s3:BucketRemoved:*"),
+    (
+        Name::ObjectReplicationFailed,
+        "s3:Replication:OperationFailedReplication",
+    ),
+    (
+        Name::ObjectReplicationComplete,
+        "s3:Replication:OperationCompletedReplication",
+    ),
+    (
+        Name::ObjectReplicationMissedThreshold,
+        "s3:Replication:OperationMissedThreshold",
+    ),
+    (
+        Name::ObjectReplicationReplicatedAfterThreshold,
+        "s3:Replication:OperationReplicatedAfterThreshold",
+    ),
+    (
+        Name::ObjectReplicationNotTracked,
+        "s3:Replication:OperationNotTracked",
+    ),
+    (Name::ObjectRestorePost, "s3:ObjectRestore:Post"),
+    (Name::ObjectRestoreCompleted, "s3:ObjectRestore:Completed"),
+    (Name::ObjectTransitionFailed, "s3:ObjectTransition:Failed"),
+    (
+        Name::ObjectTransitionComplete,
+        "s3:ObjectTransition:Complete",
+    ),
+    (Name::ObjectManyVersions, "s3:Scanner:ManyVersions"),
+    (Name::ObjectLargeVersions, "s3:Scanner:LargeVersions"),
+    (Name::PrefixManyFolders, "s3:Scanner:BigPrefix"),
+    (
+        Name::IlmDelMarkerExpirationDelete,
+        "s3:LifecycleDelMarkerExpiration:Delete",
+    ),
+    (Name::ObjectAccessedAll, "s3:ObjectAccessed:*"),
+    (Name::ObjectCreatedAll, "s3:ObjectCreated:*"),
+    (Name::ObjectRemovedAll, "s3:ObjectRemoved:*"),
+    (Name::ObjectReplicationAll, "s3:Replication:*"),
+    (Name::ObjectRestoreAll, "s3:ObjectRestore:*"),
+    (Name::ObjectTransitionAll, "s3:ObjectTransition:*"),
+    (Name::ObjectScannerAll, "s3:Scanner:*"),
+    (Name::Everything, "s3:*"),
+];
+
 impl Name {
+    /// The last name that is not a wildcard, wildcards follow it.
+    const LAST_SINGLE: Name = Name::IlmDelMarkerExpirationDelete;
+
+    /// Returns the single names covered by this name, wildcards are expanded.
     pub fn expand(&self) -> Vec<Name> {
         match self {
             Name::ObjectAccessedAll => vec![
@@ -405,24 +620,25 @@ impl Name {
                 Name::ObjectLargeVersions,
                 Name::PrefixManyFolders,
             ],
-            Name::Everything => (1..=Name::IlmDelMarkerExpirationDelete as u32)
-                .map(|i| Name::from_repr(i).unwrap())
+            Name::Everything => (1..=Name::LAST_SINGLE as u32)
+                .filter_map(Name::from_repr)
                 .collect(),
             _ => vec![*self],
         }
     }
 
+    /// Returns the bit mask of the single names this name covers, bit `n - 1` standing for the
+    /// name with discriminant `n`.
     pub fn mask(&self) -> u64 {
-        if (*self as u32) < Name::ObjectAccessedAll as u32 {
+        if *self as u32 <= Name::LAST_SINGLE as u32 {
             1 << (*self as u32 - 1)
         } else {
-            self.expand()
-                .iter()
-                .fold(0, |acc, n| acc | (1 << (*n as u32 - 1)))
+            self.expand().iter().fold(0, |acc, n| acc | n.mask())
         }
     }
 
-    fn from_repr(discriminant: u32) -> Option<Self> {
+    /// Returns the name with the given discriminant, discriminants start at 1.
+    pub fn from_repr(discriminant: u32) -> Option<Self> {
         match discriminant {
             1 => Some(Name::ObjectAccessedGet),
             2 => Some(Name::ObjectAccessedGetRetention),
@@ -467,4 +683,157 @@ impl Name {
             _ => None,
         }
     }
+
+    /// Parses either the `OBJECT_CREATED_PUT` or the `s3:ObjectCreated:Put` form of a name.
+    pub fn parse(s: &str) -> Result<Name, Error> {
+        Name::from_s3_str(s).or_else(|_| {
+            s.parse()
+                .map_err(|_| Error::UnknownEventName(s.to_string()))
+        })
+    }
+
+    /// Returns the canonical S3 event type, e.g. `s3:ObjectCreated:Put` or `s3:ObjectCreated:*`.
+    pub fn as_s3_str(&self) -> &'static str {
+        S3_NAMES
+            .iter()
+            .find(|(name, _)| name == self)
+            .map(|(_, s3)| *s3)
+            .expect("every name has an S3 event type")
+    }
+
+    /// Parses a canonical S3 event type, wildcard forms map onto the matching `*All` name.
+    pub fn from_s3_str(s: &str) -> Result<Name, Error> {
+        S3_NAMES
+            .iter()
+            .find(|(_, s3)| *s3 == s)
+            .map(|(name, _)| *name)
+            .ok_or_else(|| Error::UnknownEventName(s.to_string()))
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = Cow::<str>::deserialize(deserializer)?;
+        Name::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A set of event names stored as a bitset of single names.
+///
+/// Wildcards such as [`Name::ObjectCreatedAll`] are expanded on insertion, so a set built from
+/// `ObjectCreatedAll` contains `ObjectCreatedPut` and iterating it yields the single names only.
+/// Serializes as a list of names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NameSet(u64);
+
+impl NameSet {
+    /// Creates an empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Creates the set holding every single name.
+    pub fn all() -> Self {
+        Self(Name::Everything.mask())
+    }
+
+    /// Returns the underlying bit mask, see [`Name::mask`].
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Adds `name`, or every name it covers when it is a wildcard.
+    pub fn insert(&mut self, name: Name) {
+        self.0 |= name.mask();
+    }
+
+    /// Removes `name`, or every name it covers when it is a wildcard.
+    pub fn remove(&mut self, name: Name) {
+        self.0 &= !name.mask();
+    }
+
+    /// Determine whether every name covered by `name` is in the set.
+    pub fn contains(&self, name: Name) -> bool {
+        let mask = name.mask();
+        self.0 & mask == mask
+    }
+
+    /// Returns the names in either set.
+    pub fn union(&self, other: NameSet) -> NameSet {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the names in both sets.
+    pub fn intersection(&self, other: NameSet) -> NameSet {
+        Self(self.0 & other.0)
+    }
+
+    /// Returns `true` if the set holds no name.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the number of single names in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterates over the single names in the set, in discriminant order.
+    pub fn iter(&self) -> impl Iterator<Item = Name> + '_ {
+        (1..=Name::LAST_SINGLE as u32)
+            .filter(|n| self.0 & (1 << (n - 1)) != 0)
+            .filter_map(Name::from_repr)
+    }
+}
+
+impl From<Name> for NameSet {
+    fn from(name: Name) -> Self {
+        Self(name.mask())
+    }
+}
+
+impl FromIterator<Name> for NameSet {
+    fn from_iter<I: IntoIterator<Item = Name>>(iter: I) -> Self {
+        let mut set = NameSet::new();
+        for name in iter {
+            set.insert(name);
+        }
+        set
+    }
+}
+
+impl BitOr for NameSet {
+    type Output = NameSet;
+
+    fn bitor(self, rhs: NameSet) -> NameSet {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for NameSet {
+    type Output = NameSet;
+
+    fn bitand(self, rhs: NameSet) -> NameSet {
+        self.intersection(rhs)
+    }
+}
+
+impl Serialize for NameSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for NameSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<Name>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
 }
@@ -1,8 +1,10 @@
-use crate::{ChannelAdapter, Error, Event, NotificationConfig, NotificationSystem};
+use crate::{ChannelAdapter, Error, Event, EventSender, NotificationConfig, NotificationSystem};
 use std::sync::Arc;
 use tokio::sync::{Mutex, OnceCell};
 
 static GLOBAL_SYSTEM: OnceCell<Arc<Mutex<NotificationSystem>>> = OnceCell::const_new();
+// kept apart from the system, which stays locked while it runs
+static GLOBAL_SENDER: OnceCell<EventSender> = OnceCell::const_new();
 static INITIALIZED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
 
 /// initialize the global notification system
@@ -11,7 +13,9 @@ pub async fn initialize(config: NotificationConfig) -> Result<(), Error> {
         return Err(Error::custom("notify the system has been initialized"));
     }
 
-    let system = Arc::new(Mutex::new(NotificationSystem::new(config).await?));
+    let system = NotificationSystem::new(config).await?;
+    let _ = GLOBAL_SENDER.set(system.sender());
+    let system = Arc::new(Mutex::new(system));
     GLOBAL_SYSTEM
         .set(system)
         .map_err(|_| Error::custom("unable to set up global notification system"))?;
@@ -52,6 +56,12 @@ pub async fn start(adapters: Vec<Arc<dyn ChannelAdapter>>) -> Result<(), Error>
 ///     store_path: "./events".to_string(),
 ///     channel_capacity: 100,
 ///     adapters: vec![/* 适配器配置 */],
+///     rules: Vec::new(),
+///     store: Default::default(),
+///     dead_letter_dir: None,
+///     health_check_interval_ms: 30000,
+///     subscriber_capacity: 1024,
+///     overflow: Default::default(),
 ///     http: Default::default(),
 /// };
 ///
@@ -75,9 +85,12 @@ pub async fn initialize_and_start(config: NotificationConfig) -> Result<(), Erro
 
 /// send events to notification system
 pub async fn send_event(event: Event) -> Result<(), Error> {
-    let system = get_system().await?;
-    let system_guard = system.lock().await;
-    system_guard.send_event(event).await
+    get_sender()?.send(event).await
+}
+
+/// send events to notification system without waiting, fails with `Error::ChannelFull` when full
+pub fn try_send_event(event: Event) -> Result<(), Error> {
+    get_sender()?.try_send(event)
 }
 
 /// turn off the notification system
@@ -91,6 +104,13 @@ pub fn shutdown() -> Result<(), Error> {
     }
 }
 
+/// get the sender of the system
+fn get_sender() -> Result<&'static EventSender, Error> {
+    GLOBAL_SENDER
+        .get()
+        .ok_or_else(|| Error::custom("notification system not initialized"))
+}
+
 /// get system instance
 async fn get_system() -> Result<Arc<Mutex<NotificationSystem>>, Error> {
     GLOBAL_SYSTEM
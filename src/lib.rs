@@ -1,53 +1,114 @@
 mod adapter;
+mod arn;
 mod bus;
+mod channel;
 mod config;
 mod error;
 mod event;
 mod global;
 mod producer;
+mod retry;
+mod rules;
+mod status;
 mod store;
+mod subscription;
+mod xml;
 
 pub use adapter::ChannelAdapter;
+#[cfg(feature = "amqp")]
+pub use adapter::amqp::AmqpAdapter;
+pub use adapter::breaker::{CircuitBreaker, CircuitState};
 pub use adapter::create_adapters;
+pub use adapter::health::HealthCheck;
 #[cfg(feature = "kafka")]
 pub use adapter::kafka::KafkaAdapter;
 #[cfg(feature = "mqtt")]
 pub use adapter::mqtt::MqttAdapter;
+#[cfg(feature = "mysql")]
+pub use adapter::mysql::MysqlAdapter;
+#[cfg(feature = "nats")]
+pub use adapter::nats::NatsAdapter;
+#[cfg(feature = "postgres")]
+pub use adapter::postgres::PostgresAdapter;
+#[cfg(feature = "redis")]
+pub use adapter::redis::RedisAdapter;
 #[cfg(feature = "webhook")]
 pub use adapter::webhook::WebhookAdapter;
+pub use arn::TargetArn;
 pub use bus::event_bus;
+pub use channel::EventSender;
 #[cfg(feature = "http-producer")]
 pub use config::HttpProducerConfig;
 #[cfg(feature = "kafka")]
 pub use config::KafkaConfig;
 #[cfg(feature = "mqtt")]
 pub use config::MqttConfig;
+#[cfg(feature = "mysql")]
+pub use config::MysqlConfig;
+#[cfg(feature = "nats")]
+pub use config::NatsConfig;
+#[cfg(feature = "postgres")]
+pub use config::PostgresConfig;
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub use config::TableFormat;
 #[cfg(feature = "webhook")]
 pub use config::WebhookConfig;
-pub use config::{AdapterConfig, NotificationConfig};
+pub use config::{
+    AdapterConfig, BatchConfig, CircuitBreakerConfig, FsyncPolicy, NotificationConfig,
+    OverflowConfig, OverflowPolicy, PayloadFormat, StoreBackend, StoreConfig, TargetConfig,
+};
+#[cfg(feature = "amqp")]
+pub use config::{AmqpConfig, ExchangeType};
+#[cfg(feature = "redis")]
+pub use config::{RedisConfig, RedisMode};
 pub use error::Error;
 
-pub use event::{Bucket, Event, EventBuilder, Identity, Log, Metadata, Name, Object, Source};
-pub use global::{initialize, initialize_and_start, send_event, shutdown, start};
-pub use store::EventStore;
+pub use event::{
+    Bucket, Event, EventBuilder, Identity, Log, Metadata, Name, NameFormat, NameSet, Object,
+    S3Notification, S3Record, Source,
+};
+pub use global::{initialize, initialize_and_start, send_event, shutdown, start, try_send_event};
+pub use retry::{RetryOn, RetryPolicy};
+pub use rules::{NotificationRule, RulesMap};
+pub use status::{StatusHandle, TargetStatus};
+pub use store::dead_letter::{DeadLetter, DeadLetterStore};
+pub use store::file::FileStore;
+/// Former name of [`FileStore`].
+#[deprecated(since = "0.4.2", note = "renamed to `FileStore`")]
+pub type EventStore = FileStore;
+pub use store::memory::MemoryStore;
+pub use store::queue::QueueStore;
+#[cfg(feature = "sqlite")]
+pub use store::sqlite::SqliteStore;
+pub use store::{EventStorage, PendingEvent, StoreStats, create_storage};
+pub use subscription::{LagPolicy, Subscription, SubscriptionFilter};
+pub use xml::{NotificationConfiguration, TargetConfiguration};
 
 #[cfg(feature = "http-producer")]
 pub use producer::EventProducer;
 #[cfg(feature = "http-producer")]
 pub use producer::http::HttpProducer;
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 /// The `NotificationSystem` struct represents the notification system.
 /// It manages the event bus and the adapters.
 /// It is responsible for sending and receiving events.
 /// It also handles the shutdown process.
 pub struct NotificationSystem {
-    tx: mpsc::Sender<Event>,
+    tx: EventSender,
     rx: Option<mpsc::Receiver<Event>>,
-    store: Arc<EventStore>,
+    store: Arc<dyn EventStorage>,
+    dead_letters: Arc<DeadLetterStore>,
+    status: StatusHandle,
+    health_check_interval: Duration,
+    adapter_configs: Vec<AdapterConfig>,
+    rules: Arc<RulesMap>,
     shutdown: CancellationToken,
     #[cfg(feature = "http-producer")]
     http_config: HttpProducerConfig,
@@ -56,24 +117,30 @@ pub struct NotificationSystem {
 impl NotificationSystem {
     /// Creates a new `NotificationSystem` instance.
     pub async fn new(config: NotificationConfig) -> Result<Self, Error> {
-        let (tx, rx) = mpsc::channel::<Event>(config.channel_capacity);
-        let store = Arc::new(EventStore::new(&config.store_path).await?);
+        config.validate_rules().map_err(Error::ConfigError)?;
+        let (tx, rx) = EventSender::new(
+            config.channel_capacity,
+            config.subscriber_capacity,
+            &config.overflow,
+            &config.store_path,
+        )
+        .await?;
+        let store = create_storage(&config.store_path, config.store).await?;
+        let dead_letter_dir = config
+            .dead_letter_dir
+            .unwrap_or_else(|| format!("{}/dead_letters", config.store_path));
+        let dead_letters = Arc::new(DeadLetterStore::new(&dead_letter_dir).await?);
         let shutdown = CancellationToken::new();
 
-        let restored_logs = store.load_logs().await?;
-        for log in restored_logs {
-            for event in log.records {
-                // For example, where the send method may return a SendError when calling it
-                tx.send(event)
-                    .await
-                    .map_err(|e| Error::ChannelSend(Box::new(e)))?;
-            }
-        }
-
         Ok(Self {
             tx,
             rx: Some(rx),
             store,
+            dead_letters,
+            status: StatusHandle::default(),
+            health_check_interval: Duration::from_millis(config.health_check_interval_ms),
+            adapter_configs: config.adapters,
+            rules: Arc::new(RulesMap::new(config.rules)),
             shutdown,
             #[cfg(feature = "http-producer")]
             http_config: config.http,
@@ -85,17 +152,68 @@ impl NotificationSystem {
     pub async fn start(&mut self, adapters: Vec<Arc<dyn ChannelAdapter>>) -> Result<(), Error> {
         let rx = self.rx.take().ok_or_else(|| Error::EventBusStarted)?;
 
+        // per adapter delivery settings, keyed by the id events are routed by
+        let mut targets = HashMap::new();
+        for config in &self.adapter_configs {
+            targets.entry(config.id()).or_insert_with(|| TargetConfig {
+                retry: Some(config.retry_policy()),
+                ..config.target().clone()
+            });
+        }
+
+        // guard the adapters with their circuit breaker and health check and report them all in
+        // the status
+        let adapters = adapters
+            .into_iter()
+            .map(|adapter| {
+                let breaker = targets
+                    .get(&adapter.id())
+                    .and_then(|target| target.circuit_breaker.as_ref())
+                    .map(|config| Arc::new(CircuitBreaker::new(adapter.clone(), config)));
+                let guarded = match &breaker {
+                    Some(breaker) => breaker.clone() as Arc<dyn ChannelAdapter>,
+                    None => adapter.clone(),
+                };
+                let health = (!self.health_check_interval.is_zero())
+                    .then(|| Arc::new(HealthCheck::new(guarded.clone())));
+                self.status.register(adapter, breaker, health.clone());
+                match health {
+                    Some(health) => {
+                        tokio::spawn(
+                            health
+                                .clone()
+                                .run(self.health_check_interval, self.shutdown.clone()),
+                        );
+                        health as Arc<dyn ChannelAdapter>
+                    }
+                    None => guarded,
+                }
+            })
+            .collect();
+
         let shutdown_clone = self.shutdown.clone();
         let store_clone = self.store.clone();
+        let rules = self.rules.clone();
+        let dead_letters = Some(self.dead_letters.clone());
         let bus_handle = tokio::spawn(async move {
-            if let Err(e) = event_bus(rx, adapters, store_clone, shutdown_clone).await {
+            if let Err(e) = event_bus(
+                rx,
+                adapters,
+                store_clone,
+                targets,
+                rules,
+                dead_letters,
+                shutdown_clone,
+            )
+            .await
+            {
                 tracing::error!("Event bus failed: {}", e);
             }
         });
 
         #[cfg(feature = "http-producer")]
         {
-            let producer = HttpProducer::new(self.tx.clone(), self.http_config.port);
+            let producer = HttpProducer::with_config(self.tx.clone(), self.http_config.clone());
             producer.start().await?;
         }
 
@@ -112,13 +230,63 @@ impl NotificationSystem {
     }
 
     /// Sends an event to the notification system.
-    /// This method is used to send events to the event bus.
+    /// This method is used to send events to the event bus, applying the overflow policy when
+    /// `channel_capacity` events are already waiting.
     pub async fn send_event(&self, event: Event) -> Result<(), Error> {
-        self.tx
-            .send(event)
-            .await
-            .map_err(|e| Error::ChannelSend(Box::new(e)))?;
-        Ok(())
+        self.tx.send(event).await
+    }
+
+    /// Sends an event without waiting, failing with [`Error::ChannelFull`] when the channel is full.
+    pub fn try_send_event(&self, event: Event) -> Result<(), Error> {
+        self.tx.try_send(event)
+    }
+
+    /// Subscribes to the events sent to the system that pass `filter`, usable while the system runs.
+    ///
+    /// Subscriptions receive events as they are handed to the event bus, whether or not an adapter
+    /// is interested in them.
+    pub fn subscribe(&self, filter: SubscriptionFilter) -> Subscription {
+        self.tx.subscribe(filter)
+    }
+
+    /// Returns a sender feeding the event bus, usable while the system runs.
+    pub fn sender(&self) -> EventSender {
+        self.tx.clone()
+    }
+
+    /// Returns a handle reporting the status of the targets, usable while the system runs.
+    pub fn status(&self) -> StatusHandle {
+        self.status.clone()
+    }
+
+    /// Returns the dead-letter store, usable while the system runs.
+    pub fn dead_letter_store(&self) -> Arc<DeadLetterStore> {
+        self.dead_letters.clone()
+    }
+
+    /// Lists the deliveries that failed for good, oldest first.
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, Error> {
+        self.dead_letters.list().await
+    }
+
+    /// Returns the dead letter `id`, `None` if there is none.
+    pub async fn dead_letter(&self, id: Uuid) -> Result<Option<DeadLetter>, Error> {
+        self.dead_letters.get(id).await
+    }
+
+    /// Delivers the dead letter `id` to its target again, it is removed once delivered.
+    pub async fn requeue_dead_letter(&self, id: Uuid) -> Result<(), Error> {
+        self.dead_letters.requeue(id).await
+    }
+
+    /// Removes the dead letter `id`, returns `false` if there was none.
+    pub async fn remove_dead_letter(&self, id: Uuid) -> Result<bool, Error> {
+        self.dead_letters.remove(id).await
+    }
+
+    /// Removes every dead letter and returns how many were removed.
+    pub async fn purge_dead_letters(&self) -> Result<usize, Error> {
+        self.dead_letters.purge().await
     }
 
     /// Shuts down the notification system.
@@ -17,40 +17,108 @@ pub trait EventProducer: Send + Sync {
 #[cfg(feature = "http-producer")]
 pub mod http {
     use super::*;
-    use axum::{Json, Router, routing::post};
+    use crate::{
+        EventSender, HttpProducerConfig, NameFormat, PayloadFormat, Subscription,
+        SubscriptionFilter,
+    };
+    use axum::body::{Body, Bytes};
+    use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+    use axum::extract::{RawQuery, State};
+    use axum::http::{HeaderMap, StatusCode, header};
+    use axum::response::sse::{self, KeepAlive, Sse};
+    use axum::response::{IntoResponse, Response};
+    use axum::{Json, Router, routing::get, routing::post};
+    use std::convert::Infallible;
     use std::sync::Arc;
-    use tokio::sync::mpsc;
+    use std::time::Duration;
+    use subtle::ConstantTimeEq;
+    use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+    use tokio_stream::wrappers::IntervalStream;
+    use tokio_stream::{Stream, StreamExt};
 
+    /// Interval at which idle streaming connections are sent a keep-alive.
+    const LISTEN_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+    /// Serves `POST /event` to send events and the streaming endpoints `GET /listen` (NDJSON),
+    /// `GET /listen/sse` (Server-Sent Events) and `GET /listen/ws` (WebSocket).
+    ///
+    /// The streaming endpoints take the filter parameters of [`SubscriptionFilter::from_query`].
+    /// At most `max_connections` of them are open at once and, with an `auth_token`, they require
+    /// it as a bearer token or, for browsers, as the `access_token` query parameter.
     #[derive(Clone)]
     pub struct HttpProducer {
-        tx: mpsc::Sender<Event>,
-        port: u16,
+        tx: EventSender,
+        config: HttpProducerConfig,
+        streams: Arc<Semaphore>,
         shutdown: Arc<tokio::sync::Notify>,
     }
 
     impl HttpProducer {
-        pub fn new(tx: mpsc::Sender<Event>, port: u16) -> Self {
+        pub fn new(tx: EventSender, port: u16) -> Self {
+            Self::with_config(
+                tx,
+                HttpProducerConfig {
+                    port,
+                    ..Default::default()
+                },
+            )
+        }
+
+        pub fn with_config(tx: EventSender, config: HttpProducerConfig) -> Self {
             Self {
                 tx,
-                port,
+                streams: Arc::new(Semaphore::new(config.max_connections)),
+                config,
                 shutdown: Arc::new(tokio::sync::Notify::new()),
             }
         }
+
+        /// Checks the credentials of a streaming request, parses its filter and takes one of the
+        /// streaming connections, the connection is released when the permit is dropped.
+        fn open_stream(
+            &self,
+            headers: &HeaderMap,
+            query: Option<&str>,
+        ) -> Result<(Subscription, OwnedSemaphorePermit), (StatusCode, String)> {
+            let query = query.unwrap_or_default();
+            if let Some(token) = &self.config.auth_token {
+                let bearer = headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "));
+                let access_token = form_urlencoded::parse(query.as_bytes())
+                    .find(|(key, _)| key == "access_token")
+                    .map(|(_, value)| value);
+                // compared in constant time so the token cannot be guessed from response times
+                let matches =
+                    |candidate: &str| bool::from(candidate.as_bytes().ct_eq(token.as_bytes()));
+                if !bearer.is_some_and(matches) && !access_token.as_deref().is_some_and(matches) {
+                    return Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string()));
+                }
+            }
+            let filter = SubscriptionFilter::from_query(query)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            let permit = self.streams.clone().try_acquire_owned().map_err(|_| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Too many streaming connections".to_string(),
+                )
+            })?;
+            Ok((self.tx.subscribe(filter), permit))
+        }
     }
 
     #[async_trait]
     impl EventProducer for HttpProducer {
         async fn start(&self) -> Result<(), Error> {
-            let producer = self.clone();
-            let app = Router::new().route(
-                "/event",
-                post(move |event| {
-                    let prod = producer.clone();
-                    async move { handle_event(event, prod).await }
-                }),
-            );
-
-            let addr = format!("0.0.0.0:{}", self.port);
+            let app = Router::new()
+                .route("/event", post(handle_event))
+                .route("/listen", get(handle_listen))
+                .route("/listen/sse", get(handle_sse))
+                .route("/listen/ws", get(handle_ws))
+                .with_state(self.clone());
+
+            let addr = format!("0.0.0.0:{}", self.config.port);
             let listener = tokio::net::TcpListener::bind(&addr).await?;
 
             let shutdown = self.shutdown.clone();
@@ -69,21 +137,151 @@ pub mod http {
         }
 
         async fn send_event(&self, event: Event) -> Result<(), Error> {
-            self.tx
-                .send(event)
-                .await
-                .map_err(|e| Error::ChannelSend(Box::new(e)))?;
-            Ok(())
+            self.tx.send(event).await
+        }
+    }
+
+    /// Serializes an event the way the streaming endpoints send it, in the S3 notification form.
+    fn stream_payload(event: &Event) -> Option<String> {
+        match event.to_payload(PayloadFormat::S3, NameFormat::S3) {
+            Ok(payload) => Some(payload),
+            Err(e) => {
+                tracing::error!("Failed to serialize event {}: {}", event.id, e);
+                None
+            }
+        }
+    }
+
+    /// Streams the events passing the filter of the query as newline-delimited S3 notification
+    /// JSON, with a blank line every [`LISTEN_KEEP_ALIVE`].
+    async fn handle_listen(
+        State(producer): State<HttpProducer>,
+        headers: HeaderMap,
+        RawQuery(query): RawQuery,
+    ) -> Response {
+        let (events, permit) = match producer.open_stream(&headers, query.as_deref()) {
+            Ok(stream) => stream,
+            Err(rejection) => return rejection.into_response(),
+        };
+        (
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(listen_body(events, permit)),
+        )
+            .into_response()
+    }
+
+    /// Returns the body of a `/listen` response, holding the streaming connection until the
+    /// events end.
+    fn listen_body(
+        events: impl Stream<Item = Event> + Send + 'static,
+        permit: OwnedSemaphorePermit,
+    ) -> impl Stream<Item = Result<Bytes, Infallible>> + Send + 'static {
+        // the keep-alive never ends, so the end of the events is marked by a `None`
+        let events = events
+            .filter_map(|event| stream_payload(&event).map(|payload| Bytes::from(payload + "\n")))
+            .map(Some)
+            .chain(tokio_stream::once(None));
+        let interval = tokio::time::interval_at(
+            tokio::time::Instant::now() + LISTEN_KEEP_ALIVE,
+            LISTEN_KEEP_ALIVE,
+        );
+        let keep_alive = IntervalStream::new(interval).map(|_| Some(Bytes::from_static(b"\n")));
+        events.merge(keep_alive).map_while(move |bytes| {
+            let _permit = &permit;
+            bytes.map(Ok)
+        })
+    }
+
+    /// Streams the events passing the filter of the query as Server-Sent Events named after the
+    /// S3 event type, with the event id as SSE id.
+    async fn handle_sse(
+        State(producer): State<HttpProducer>,
+        headers: HeaderMap,
+        RawQuery(query): RawQuery,
+    ) -> Response {
+        let (events, permit) = match producer.open_stream(&headers, query.as_deref()) {
+            Ok(stream) => stream,
+            Err(rejection) => return rejection.into_response(),
+        };
+        let events = events.filter_map(move |event| {
+            let _permit = &permit;
+            stream_payload(&event).map(|payload| {
+                Ok::<_, Infallible>(
+                    sse::Event::default()
+                        .id(event.id.to_string())
+                        .event(event.event_name.as_s3_str())
+                        .data(payload),
+                )
+            })
+        });
+        Sse::new(events)
+            .keep_alive(KeepAlive::new().interval(LISTEN_KEEP_ALIVE))
+            .into_response()
+    }
+
+    /// Streams the events passing the filter of the query as WebSocket text messages.
+    async fn handle_ws(
+        State(producer): State<HttpProducer>,
+        headers: HeaderMap,
+        RawQuery(query): RawQuery,
+        upgrade: WebSocketUpgrade,
+    ) -> Response {
+        match producer.open_stream(&headers, query.as_deref()) {
+            Ok((events, permit)) => upgrade.on_upgrade(move |socket| async move {
+                stream_ws(socket, events).await;
+                drop(permit);
+            }),
+            Err(rejection) => rejection.into_response(),
+        }
+    }
+
+    async fn stream_ws(mut socket: WebSocket, mut events: Subscription) {
+        loop {
+            tokio::select! {
+                event = events.next() => {
+                    let Some(event) = event else { break };
+                    if let Some(payload) = stream_payload(&event)
+                        && socket.send(Message::Text(payload.into())).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
         }
     }
 
     async fn handle_event(
+        State(producer): State<HttpProducer>,
         Json(event): Json<Event>,
-        producer: HttpProducer,
-    ) -> Result<(), axum::http::StatusCode> {
-        producer
-            .send_event(event)
-            .await
-            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)
+    ) -> Result<(), StatusCode> {
+        producer.send_event(event).await.map_err(|e| match e {
+            Error::ChannelFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::Name;
+        use crate::adapter::test_event;
+
+        #[tokio::test(start_paused = true)]
+        async fn test_listen_body_ends_with_the_events() {
+            let streams = Arc::new(Semaphore::new(1));
+            let permit = streams.clone().try_acquire_owned().unwrap();
+            let event = test_event(Name::ObjectCreatedPut, "photos", "a.jpg");
+            let body = listen_body(tokio_stream::iter([event]), permit);
+
+            let lines: Vec<_> = body.map(Result::unwrap).collect().await;
+            assert_eq!(lines.len(), 1);
+            assert!(String::from_utf8_lossy(&lines[0]).contains("a.jpg"));
+            // the streaming connection is released once the body is done
+            assert_eq!(streams.available_permits(), 1);
+        }
     }
 }
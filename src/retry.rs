@@ -0,0 +1,164 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::{Instant, sleep};
+
+/// Which failed deliveries are retried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryOn {
+    /// errors reported as retryable by [`Error::is_retryable`], such as timeouts or HTTP 5xx
+    #[default]
+    Transient,
+    /// every error
+    All,
+}
+
+/// How a failed delivery is retried, applied by the event bus around every `ChannelAdapter::send`.
+///
+/// The delay before retry `n` is `base_delay_ms * 2^(n - 1)` capped at `max_delay_ms`; with `jitter`
+/// a random delay between half and all of it is used instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Number of send attempts, the first one included
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default = "default_jitter")]
+    pub jitter: bool,
+    /// Time in milliseconds after the first attempt past which no retry is started
+    #[serde(default)]
+    pub deadline_ms: Option<u64>,
+    #[serde(default)]
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            base_delay_ms: default_base_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            jitter: default_jitter(),
+            deadline_ms: None,
+            retry_on: RetryOn::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that makes a single attempt.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// verify that the policy is valid
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("Retry max_attempts must be greater than 0".to_string());
+        }
+        if self.base_delay_ms > self.max_delay_ms {
+            return Err("Retry base_delay_ms cannot exceed max_delay_ms".to_string());
+        }
+        Ok(())
+    }
+
+    /// Determine whether a delivery that failed with `error` may be retried.
+    ///
+    /// A send rejected because the target is unavailable is never retried.
+    pub fn should_retry(&self, error: &Error) -> bool {
+        if error.is_unavailable() {
+            return false;
+        }
+        match self.retry_on {
+            RetryOn::Transient => error.is_retryable(),
+            RetryOn::All => true,
+        }
+    }
+
+    /// Returns the delay before retry `retry`, counting from 1.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        let delay = self
+            .base_delay_ms
+            .saturating_mul(factor)
+            .min(self.max_delay_ms);
+        let delay = if self.jitter && delay > 0 {
+            fastrand::u64(delay / 2..=delay)
+        } else {
+            delay
+        };
+        Duration::from_millis(delay)
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error that is not retried, runs out of
+    /// attempts or would retry past the deadline.
+    ///
+    /// Returns the outcome of the last attempt.
+    pub async fn run<T, F, Fut>(&self, operation: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.run_with_attempts(operation).await.0
+    }
+
+    /// Same as [`run`](Self::run), also returning the number of attempts made.
+    pub async fn run_with_attempts<T, F, Fut>(&self, mut operation: F) -> (Result<T, Error>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let deadline = self
+            .deadline_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        let mut attempt = 1;
+        loop {
+            let error = match operation().await {
+                Ok(value) => return (Ok(value), attempt),
+                Err(e) => e,
+            };
+            if attempt >= self.max_attempts || !self.should_retry(&error) {
+                return (Err(error), attempt);
+            }
+            let delay = self.delay(attempt);
+            if deadline.is_some_and(|deadline| Instant::now() + delay > deadline) {
+                return (Err(error), attempt);
+            }
+            tracing::warn!(
+                "Attempt {} failed: {}. Retrying in {:?}...",
+                attempt,
+                error,
+                delay
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Provides the default number of send attempts
+fn default_max_attempts() -> u32 {
+    3
+}
+
+/// Provides the default delay before the first retry
+fn default_base_delay_ms() -> u64 {
+    1000
+}
+
+/// Provides the default upper bound of the retry delay
+fn default_max_delay_ms() -> u64 {
+    30000
+}
+
+/// Provides the default jitter setting
+fn default_jitter() -> bool {
+    true
+}
@@ -0,0 +1,95 @@
+use crate::{Event, Name, NameSet};
+use serde::{Deserialize, Serialize};
+
+/// A bucket notification rule, the equivalent of one S3 queue/topic configuration.
+///
+/// An event matches when its name is covered by one of `events` (wildcards such as
+/// [`Name::ObjectCreatedAll`] included), it happened in `bucket` if one is given, and its object
+/// key starts with `prefix` and ends with `suffix`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    /// Identifier of the rule, reported as `configurationId` by S3
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Bucket the rule applies to, every bucket when unset
+    #[serde(default)]
+    pub bucket: Option<String>,
+    pub events: Vec<Name>,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+    /// Ids of the adapters matching events are delivered to
+    pub targets: Vec<String>,
+}
+
+impl NotificationRule {
+    /// verify that the rule is valid
+    pub fn validate(&self) -> Result<(), String> {
+        if self.events.is_empty() {
+            return Err("Notification rule must list at least one event".to_string());
+        }
+        if self.targets.is_empty() {
+            return Err("Notification rule must list at least one target".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the single event names selected by this rule.
+    pub fn event_set(&self) -> NameSet {
+        self.events.iter().copied().collect()
+    }
+
+    /// Determine whether an event is selected by this rule
+    pub fn matches(&self, event: &Event) -> bool {
+        if self
+            .bucket
+            .as_ref()
+            .is_some_and(|bucket| *bucket != event.s3.bucket.name)
+        {
+            return false;
+        }
+        if !self.event_set().contains(event.event_name) {
+            return false;
+        }
+        let key = &event.s3.object.key;
+        self.prefix.as_ref().is_none_or(|p| key.starts_with(p))
+            && self.suffix.as_ref().is_none_or(|s| key.ends_with(s))
+    }
+}
+
+/// The notification rules evaluated by the event bus.
+#[derive(Debug, Clone, Default)]
+pub struct RulesMap {
+    rules: Vec<NotificationRule>,
+}
+
+impl RulesMap {
+    /// Creates a rules map from the provided rules.
+    pub fn new(rules: Vec<NotificationRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Returns `true` if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the rules in the map.
+    pub fn rules(&self) -> &[NotificationRule] {
+        &self.rules
+    }
+
+    /// Returns the ids of every target selected by a rule matching `event`, without duplicates.
+    pub fn match_targets(&self, event: &Event) -> Vec<String> {
+        let mut targets: Vec<String> = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.matches(event)) {
+            for target in &rule.targets {
+                if !targets.contains(target) {
+                    targets.push(target.clone());
+                }
+            }
+        }
+        targets
+    }
+}
@@ -0,0 +1,85 @@
+use crate::ChannelAdapter;
+use crate::adapter::breaker::{CircuitBreaker, CircuitState};
+use crate::adapter::health::HealthCheck;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Delivery status of one target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetStatus {
+    pub id: String,
+    /// Kind of the adapter, e.g. `webhook`
+    pub name: String,
+    /// State of the circuit breaker, `None` when the target has none
+    pub circuit: Option<CircuitState>,
+    /// Number of consecutive failed sends seen by the circuit breaker
+    pub consecutive_failures: u32,
+    /// Whether the last health check passed, `true` when health checks are disabled
+    pub active: bool,
+    /// When the target was last checked, `None` before the first check
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+struct Target {
+    adapter: Arc<dyn ChannelAdapter>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    health: Option<Arc<HealthCheck>>,
+}
+
+/// A cloneable view of the status of the targets of a notification system.
+///
+/// Obtained through [`NotificationSystem::status`](crate::NotificationSystem::status), it keeps
+/// reporting while the system runs. Targets are registered when the system starts.
+#[derive(Clone, Default)]
+pub struct StatusHandle {
+    targets: Arc<RwLock<Vec<Target>>>,
+}
+
+impl StatusHandle {
+    pub(crate) fn register(
+        &self,
+        adapter: Arc<dyn ChannelAdapter>,
+        breaker: Option<Arc<CircuitBreaker>>,
+        health: Option<Arc<HealthCheck>>,
+    ) {
+        self.targets
+            .write()
+            .expect("status registry poisoned")
+            .push(Target {
+                adapter,
+                breaker,
+                health,
+            });
+    }
+
+    /// Returns the status of every target.
+    pub fn targets(&self) -> Vec<TargetStatus> {
+        let targets = self.targets.read().expect("status registry poisoned");
+        targets.iter().map(Target::status).collect()
+    }
+
+    /// Returns the status of the target `id`, `None` if there is no such target.
+    pub fn target(&self, id: &str) -> Option<TargetStatus> {
+        self.targets().into_iter().find(|status| status.id == id)
+    }
+}
+
+impl Target {
+    fn status(&self) -> TargetStatus {
+        TargetStatus {
+            id: self.adapter.id(),
+            name: self.adapter.name(),
+            circuit: self.breaker.as_ref().map(|breaker| breaker.state()),
+            consecutive_failures: self
+                .breaker
+                .as_ref()
+                .map_or(0, |breaker| breaker.consecutive_failures()),
+            active: self.health.as_ref().is_none_or(|health| health.active()),
+            last_checked: self
+                .health
+                .as_ref()
+                .and_then(|health| health.last_checked()),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::Error;
+use crate::Event;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::fs::create_dir_all;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+const DEAD_LETTER_SUFFIX: &str = ".json";
+
+/// A delivery that failed for good, kept for inspection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Identifier of the dead letter, distinct from the event id
+    pub id: Uuid,
+    /// Id of the adapter the event could not be delivered to
+    pub target: String,
+    pub event: Event,
+    /// Number of send attempts made before the delivery was given up
+    pub attempts: u32,
+    /// The error of the last attempt
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    /// Creates a dead letter for the delivery of `event` to `target`.
+    pub fn new(event: Event, target: impl Into<String>, attempts: u32, error: &Error) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            target: target.into(),
+            event,
+            attempts,
+            error: error.to_string(),
+            failed_at: Utc::now(),
+        }
+    }
+}
+
+/// `DeadLetterStore` keeps the deliveries that exhausted their retries.
+///
+/// Each dead letter is stored in its own file inside the store directory until it is requeued and
+/// delivered, removed or purged. Requeue requests are handed to the event bus, which delivers the
+/// event to its target again; the dead letter is only removed once that delivery succeeds.
+pub struct DeadLetterStore {
+    dir: PathBuf,
+    requeued: Mutex<Vec<Uuid>>,
+    notify: Notify,
+}
+
+impl DeadLetterStore {
+    /// Opens the dead-letter store in `dir`.
+    pub async fn new(dir: &str) -> Result<Self, Error> {
+        create_dir_all(dir).await?;
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            requeued: Mutex::new(Vec::new()),
+            notify: Notify::new(),
+        })
+    }
+
+    /// Writes `letter`, replacing the dead letter with the same id.
+    pub async fn put(&self, letter: &DeadLetter) -> Result<(), Error> {
+        let data = serde_json::to_vec(letter)?;
+        // write to a temporary file first so that a partial entry is never picked up
+        let tmp_path = self.dir.join(format!("{}.tmp", letter.id));
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, self.entry_path(letter.id)).await?;
+        Ok(())
+    }
+
+    /// Reads the dead letter `id`, `None` if there is none.
+    pub async fn get(&self, id: Uuid) -> Result<Option<DeadLetter>, Error> {
+        match tokio::fs::read(self.entry_path(id)).await {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Lists every dead letter, oldest failure first.
+    pub async fn list(&self) -> Result<Vec<DeadLetter>, Error> {
+        let mut letters = Vec::new();
+        for id in self.ids().await? {
+            match self.get(id).await {
+                Ok(Some(letter)) => letters.push(letter),
+                Ok(None) => {}
+                Err(Error::Serde(e)) => {
+                    tracing::error!("Skipping corrupt dead letter {}: {}", id, e)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        letters.sort_by_key(|letter| letter.failed_at);
+        Ok(letters)
+    }
+
+    /// Removes the dead letter `id`, returns `false` if there was none.
+    pub async fn remove(&self, id: Uuid) -> Result<bool, Error> {
+        match tokio::fs::remove_file(self.entry_path(id)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Removes every dead letter and returns how many were removed.
+    pub async fn purge(&self) -> Result<usize, Error> {
+        let mut removed = 0;
+        for id in self.ids().await? {
+            if self.remove(id).await? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Asks the event bus to deliver the dead letter `id` to its target again.
+    ///
+    /// Returns [`Error::DeadLetterNotFound`] if there is no such dead letter.
+    pub async fn requeue(&self, id: Uuid) -> Result<(), Error> {
+        if !self.entry_path(id).exists() {
+            return Err(Error::DeadLetterNotFound(id));
+        }
+        {
+            let mut requeued = self.requeued.lock().expect("requeue list poisoned");
+            if !requeued.contains(&id) {
+                requeued.push(id);
+            }
+        }
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Waits for requeue requests and returns the dead letters to deliver again.
+    ///
+    /// Cancel safe: requests are only taken off the list once their dead letters have been read.
+    pub(crate) async fn next_requeued(&self) -> Vec<DeadLetter> {
+        loop {
+            let ids = self.requeued.lock().expect("requeue list poisoned").clone();
+            if ids.is_empty() {
+                self.notify.notified().await;
+                continue;
+            }
+            let mut letters = Vec::new();
+            for id in &ids {
+                match self.get(*id).await {
+                    Ok(Some(letter)) => letters.push(letter),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Failed to read dead letter {}: {}", id, e),
+                }
+            }
+            self.requeued
+                .lock()
+                .expect("requeue list poisoned")
+                .retain(|id| !ids.contains(id));
+            if !letters.is_empty() {
+                return letters;
+            }
+        }
+    }
+
+    async fn ids(&self) -> Result<Vec<Uuid>, Error> {
+        let mut ids = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            if let Some(id) = file_name
+                .to_string_lossy()
+                .strip_suffix(DEAD_LETTER_SUFFIX)
+                .and_then(|id| Uuid::parse_str(id).ok())
+            {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn entry_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{id}{DEAD_LETTER_SUFFIX}"))
+    }
+}
@@ -0,0 +1,459 @@
+use super::{EventStorage, PendingEvent, StoreStats};
+use crate::Error;
+use crate::Event;
+use crate::{FsyncPolicy, StoreConfig};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::fs::{File, OpenOptions, create_dir_all};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Number of retained segments that triggers a compaction of the ledger.
+const COMPACT_THRESHOLD: usize = 16;
+
+/// Size of the header preceding every record: the payload length and its CRC-32 checksum.
+const RECORD_HEADER_LEN: usize = 8;
+
+const SEGMENT_PREFIX: &str = "segment_";
+const SEGMENT_SUFFIX: &str = ".wal";
+const LEGACY_PREFIX: &str = "events_";
+
+/// A single record of the write-ahead log.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Entry {
+    /// The event was accepted and must be delivered to `targets`.
+    Append {
+        targets: Vec<String>,
+        event: Box<Event>,
+    },
+    /// The event was delivered to `target`.
+    Ack { id: Uuid, target: String },
+}
+
+struct Pending {
+    event: Event,
+    targets: Vec<String>,
+    segment: u64,
+}
+
+struct Segment {
+    seq: u64,
+    writer: BufWriter<File>,
+    bytes: u64,
+    created: Instant,
+}
+
+struct Ledger {
+    active: Segment,
+    /// Number of unresolved events per retained segment, keyed by sequence.
+    segments: BTreeMap<u64, usize>,
+    pending: HashMap<Uuid, Pending>,
+    config: StoreConfig,
+    /// Whether records were written since the last fsync.
+    dirty: bool,
+}
+
+/// `FileStore` keeps the delivery ledger in a directory of segment files.
+///
+/// Every event is appended to the ledger before it is dispatched, together with the targets it
+/// has to reach. Each successful delivery is recorded as an acknowledgement, and segments are
+/// removed once all of their events have been acknowledged by every target, so a restart only
+/// replays what is genuinely undelivered.
+///
+/// The ledger is an append-only write-ahead log of checksummed records split into segments that
+/// rotate by size and age. Records are synced to disk according to the configured
+/// [`FsyncPolicy`], and a record torn by a crash is truncated on recovery instead of failing it.
+pub struct FileStore {
+    path: PathBuf,
+    ledger: Arc<Mutex<Ledger>>,
+}
+
+impl FileStore {
+    /// Opens the ledger in `path` with the default [`StoreConfig`].
+    pub async fn new(path: &str) -> Result<Self, Error> {
+        Self::with_config(path, StoreConfig::default()).await
+    }
+
+    /// Opens the ledger in `path`, recovering any events that were not delivered yet.
+    pub async fn with_config(path: &str, config: StoreConfig) -> Result<Self, Error> {
+        config.validate().map_err(Error::ConfigError)?;
+        create_dir_all(path).await?;
+        let path = PathBuf::from(path);
+
+        let mut sequences = Vec::new();
+        let mut entries = tokio::fs::read_dir(&path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if let Some(seq) = parse_segment_name(&file_name) {
+                sequences.push(seq);
+            } else if file_name.starts_with(LEGACY_PREFIX) {
+                tracing::warn!(
+                    "Ignoring legacy event log {}, it is no longer replayed",
+                    entry.path().display()
+                );
+            }
+        }
+        sequences.sort_unstable();
+
+        let mut pending: HashMap<Uuid, Pending> = HashMap::new();
+        for seq in &sequences {
+            for entry in read_segment(&segment_path(&path, *seq)).await? {
+                match entry {
+                    Entry::Append { targets, event } => {
+                        pending.insert(
+                            event.id,
+                            Pending {
+                                event: *event,
+                                targets,
+                                segment: 0,
+                            },
+                        );
+                    }
+                    Entry::Ack { id, target } => {
+                        if let Some(p) = pending.get_mut(&id) {
+                            p.targets.retain(|t| *t != target);
+                            if p.targets.is_empty() {
+                                pending.remove(&id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Compact whatever survived into a fresh segment so that history is never read twice.
+        let next_seq = sequences.last().map_or(0, |seq| seq + 1);
+        let mut ledger = Ledger {
+            active: Segment::create(&path, next_seq).await?,
+            segments: BTreeMap::from([(next_seq, 0)]),
+            pending: HashMap::new(),
+            config,
+            dirty: false,
+        };
+        for (id, mut p) in pending {
+            p.segment = next_seq;
+            ledger
+                .active
+                .write(&Entry::Append {
+                    targets: p.targets.clone(),
+                    event: Box::new(p.event.clone()),
+                })
+                .await?;
+            ledger.pending.insert(id, p);
+        }
+        ledger.active.writer.flush().await?;
+        ledger.sync_unless_never().await?;
+        ledger.segments.insert(next_seq, ledger.pending.len());
+        for seq in sequences {
+            tokio::fs::remove_file(segment_path(&path, seq)).await?;
+        }
+        if !ledger.pending.is_empty() {
+            tracing::info!("Recovered {} undelivered events", ledger.pending.len());
+        }
+
+        let fsync_interval = (ledger.config.fsync == FsyncPolicy::Interval)
+            .then(|| Duration::from_millis(ledger.config.fsync_interval_ms));
+        let ledger = Arc::new(Mutex::new(ledger));
+        if let Some(interval) = fsync_interval {
+            tokio::spawn(sync_periodically(Arc::downgrade(&ledger), interval));
+        }
+
+        Ok(Self { path, ledger })
+    }
+
+    /// Starts a new segment once the active one exceeds its size or age limit, compacting the
+    /// ledger if too many segments are being retained by undelivered events.
+    async fn rotate_if_full(&self, ledger: &mut Ledger) -> Result<(), Error> {
+        let max_age = Duration::from_secs(ledger.config.segment_max_age_secs);
+        if ledger.active.bytes < ledger.config.segment_max_bytes
+            && ledger.active.created.elapsed() < max_age
+        {
+            return Ok(());
+        }
+        ledger.sync_unless_never().await?;
+        let seq = ledger.active.seq + 1;
+        ledger.active = Segment::create(&self.path, seq).await?;
+        ledger.segments.insert(seq, 0);
+        self.prune(ledger).await?;
+        if ledger.segments.len() > COMPACT_THRESHOLD {
+            self.compact(ledger).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the oldest segments as long as all of their events are resolved.
+    ///
+    /// Acknowledgements are always written to the active segment, so a segment may only go once
+    /// every older segment is gone too.
+    async fn prune(&self, ledger: &mut Ledger) -> Result<(), Error> {
+        while let Some((&seq, &unresolved)) = ledger.segments.first_key_value() {
+            if seq == ledger.active.seq || unresolved > 0 {
+                break;
+            }
+            tokio::fs::remove_file(segment_path(&self.path, seq)).await?;
+            ledger.segments.remove(&seq);
+        }
+        Ok(())
+    }
+
+    /// Rewrites all pending events into a fresh segment and drops every older one.
+    async fn compact(&self, ledger: &mut Ledger) -> Result<(), Error> {
+        let seq = ledger.active.seq + 1;
+        let mut segment = Segment::create(&self.path, seq).await?;
+        for p in ledger.pending.values_mut() {
+            segment
+                .write(&Entry::Append {
+                    targets: p.targets.clone(),
+                    event: Box::new(p.event.clone()),
+                })
+                .await?;
+            p.segment = seq;
+        }
+        segment.writer.flush().await?;
+
+        let retired = std::mem::take(&mut ledger.segments);
+        ledger.active = segment;
+        ledger.sync_unless_never().await?;
+        ledger.segments.insert(seq, ledger.pending.len());
+        for old in retired.into_keys() {
+            tokio::fs::remove_file(segment_path(&self.path, old)).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventStorage for FileStore {
+    async fn append(&self, event: &Event, targets: &[String]) -> Result<Vec<String>, Error> {
+        let mut ledger = self.ledger.lock().await;
+        if let Some(p) = ledger.pending.get(&event.id) {
+            return Ok(p.targets.clone());
+        }
+        if targets.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.rotate_if_full(&mut ledger).await?;
+        let entry = Entry::Append {
+            targets: targets.to_vec(),
+            event: Box::new(event.clone()),
+        };
+        ledger.active.write(&entry).await?;
+        ledger.commit().await?;
+
+        let segment = ledger.active.seq;
+        *ledger.segments.entry(segment).or_default() += 1;
+        ledger.pending.insert(
+            event.id,
+            Pending {
+                event: event.clone(),
+                targets: targets.to_vec(),
+                segment,
+            },
+        );
+        Ok(targets.to_vec())
+    }
+
+    async fn ack(&self, id: Uuid, target: &str) -> Result<(), Error> {
+        let mut ledger = self.ledger.lock().await;
+        let known = ledger
+            .pending
+            .get(&id)
+            .is_some_and(|p| p.targets.iter().any(|t| t == target));
+        if !known {
+            return Ok(());
+        }
+
+        self.rotate_if_full(&mut ledger).await?;
+        let Some(p) = ledger.pending.get_mut(&id) else {
+            return Ok(());
+        };
+        p.targets.retain(|t| t != target);
+        let resolved = p.targets.is_empty().then_some(p.segment);
+
+        let entry = Entry::Ack {
+            id,
+            target: target.to_string(),
+        };
+        ledger.active.write(&entry).await?;
+        ledger.commit().await?;
+
+        if let Some(segment) = resolved {
+            ledger.pending.remove(&id);
+            if let Some(count) = ledger.segments.get_mut(&segment) {
+                *count = count.saturating_sub(1);
+            }
+            self.prune(&mut ledger).await?;
+        }
+        Ok(())
+    }
+
+    async fn load_pending(&self) -> Result<Vec<PendingEvent>, Error> {
+        let ledger = self.ledger.lock().await;
+        let mut pending: Vec<PendingEvent> = ledger
+            .pending
+            .values()
+            .map(|p| PendingEvent {
+                event: p.event.clone(),
+                targets: p.targets.clone(),
+            })
+            .collect();
+        pending.sort_by_key(|p| p.event.timestamp);
+        Ok(pending)
+    }
+
+    async fn purge(&self) -> Result<usize, Error> {
+        let mut ledger = self.ledger.lock().await;
+        let purged = ledger.pending.len();
+        ledger.pending.clear();
+        self.compact(&mut ledger).await?;
+        Ok(purged)
+    }
+
+    async fn stats(&self) -> Result<StoreStats, Error> {
+        let ledger = self.ledger.lock().await;
+        let mut disk_bytes = 0;
+        for seq in ledger.segments.keys() {
+            disk_bytes += tokio::fs::metadata(segment_path(&self.path, *seq))
+                .await?
+                .len();
+        }
+        Ok(StoreStats {
+            pending_events: ledger.pending.len(),
+            pending_deliveries: ledger.pending.values().map(|p| p.targets.len()).sum(),
+            disk_bytes,
+        })
+    }
+}
+
+impl Ledger {
+    /// Flushes the records written to the active segment and syncs them if the policy says so.
+    async fn commit(&mut self) -> Result<(), Error> {
+        self.active.writer.flush().await?;
+        self.dirty = true;
+        if self.config.fsync == FsyncPolicy::Always {
+            self.sync().await?;
+        }
+        Ok(())
+    }
+
+    /// Syncs the active segment unless syncing is disabled altogether.
+    async fn sync_unless_never(&mut self) -> Result<(), Error> {
+        if self.config.fsync == FsyncPolicy::Never {
+            return Ok(());
+        }
+        self.sync().await
+    }
+
+    async fn sync(&mut self) -> Result<(), Error> {
+        self.active.writer.flush().await?;
+        self.active.writer.get_ref().sync_data().await?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl Segment {
+    async fn create(dir: &Path, seq: u64) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(dir, seq))
+            .await?;
+        Ok(Self {
+            seq,
+            writer: BufWriter::new(file),
+            bytes: 0,
+            created: Instant::now(),
+        })
+    }
+
+    /// Writes `entry` as a record framed by its length and CRC-32 checksum.
+    async fn write(&mut self, entry: &Entry) -> Result<(), Error> {
+        let payload = serde_json::to_vec(entry)?;
+        let len = u32::try_from(payload.len())
+            .map_err(|_| Error::custom("event store record exceeds 4 GiB"))?;
+        self.writer.write_all(&len.to_le_bytes()).await?;
+        self.writer
+            .write_all(&crc32fast::hash(&payload).to_le_bytes())
+            .await?;
+        self.writer.write_all(&payload).await?;
+        self.bytes += (RECORD_HEADER_LEN + payload.len()) as u64;
+        Ok(())
+    }
+}
+
+/// Syncs the ledger every `interval` while records are pending, until the store is dropped.
+async fn sync_periodically(ledger: std::sync::Weak<Mutex<Ledger>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let Some(ledger) = ledger.upgrade() else {
+            break;
+        };
+        let mut ledger = ledger.lock().await;
+        if ledger.dirty
+            && let Err(e) = ledger.sync().await
+        {
+            tracing::error!("Failed to sync event store: {}", e);
+        }
+    }
+}
+
+/// Reads every intact record of a segment.
+///
+/// Reading stops at the first truncated or corrupt record, which can only be the tail torn by a
+/// crash; the segment is truncated there so that it can be appended to again.
+async fn read_segment(path: &Path) -> Result<Vec<Entry>, Error> {
+    let data = tokio::fs::read(path).await?;
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let Some((entry, len)) = decode_record(&data[offset..]) else {
+            tracing::warn!(
+                "Truncating torn tail of {} at offset {} ({} bytes dropped)",
+                path.display(),
+                offset,
+                data.len() - offset
+            );
+            let file = OpenOptions::new().write(true).open(path).await?;
+            file.set_len(offset as u64).await?;
+            file.sync_all().await?;
+            break;
+        };
+        entries.push(entry);
+        offset += len;
+    }
+    Ok(entries)
+}
+
+/// Decodes the record at the start of `buf`, returning it with its encoded length.
+fn decode_record(buf: &[u8]) -> Option<(Entry, usize)> {
+    let header = buf.get(..RECORD_HEADER_LEN)?;
+    let len = u32::from_le_bytes(header[..4].try_into().ok()?) as usize;
+    let checksum = u32::from_le_bytes(header[4..].try_into().ok()?);
+    let payload = buf.get(RECORD_HEADER_LEN..RECORD_HEADER_LEN + len)?;
+    if crc32fast::hash(payload) != checksum {
+        return None;
+    }
+    let entry = serde_json::from_slice(payload).ok()?;
+    Some((entry, RECORD_HEADER_LEN + len))
+}
+
+fn segment_path(dir: &Path, seq: u64) -> PathBuf {
+    dir.join(format!("{SEGMENT_PREFIX}{seq:020}{SEGMENT_SUFFIX}"))
+}
+
+fn parse_segment_name(name: &str) -> Option<u64> {
+    name.strip_prefix(SEGMENT_PREFIX)?
+        .strip_suffix(SEGMENT_SUFFIX)?
+        .parse()
+        .ok()
+}
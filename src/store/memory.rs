@@ -0,0 +1,76 @@
+use super::{EventStorage, PendingEvent, StoreStats};
+use crate::Error;
+use crate::Event;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// `MemoryStore` keeps the delivery ledger in memory.
+///
+/// Nothing survives a restart, which makes it suitable for tests and for deployments that do not
+/// need delivery guarantees across restarts.
+#[derive(Default)]
+pub struct MemoryStore {
+    pending: Mutex<HashMap<Uuid, PendingEvent>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EventStorage for MemoryStore {
+    async fn append(&self, event: &Event, targets: &[String]) -> Result<Vec<String>, Error> {
+        let mut pending = self.pending.lock().await;
+        if let Some(p) = pending.get(&event.id) {
+            return Ok(p.targets.clone());
+        }
+        if !targets.is_empty() {
+            pending.insert(
+                event.id,
+                PendingEvent {
+                    event: event.clone(),
+                    targets: targets.to_vec(),
+                },
+            );
+        }
+        Ok(targets.to_vec())
+    }
+
+    async fn ack(&self, id: Uuid, target: &str) -> Result<(), Error> {
+        let mut pending = self.pending.lock().await;
+        if let Some(p) = pending.get_mut(&id) {
+            p.targets.retain(|t| t != target);
+            if p.targets.is_empty() {
+                pending.remove(&id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_pending(&self) -> Result<Vec<PendingEvent>, Error> {
+        let mut pending: Vec<PendingEvent> = self.pending.lock().await.values().cloned().collect();
+        pending.sort_by_key(|p| p.event.timestamp);
+        Ok(pending)
+    }
+
+    async fn purge(&self) -> Result<usize, Error> {
+        let mut pending = self.pending.lock().await;
+        let purged = pending.len();
+        pending.clear();
+        Ok(purged)
+    }
+
+    async fn stats(&self) -> Result<StoreStats, Error> {
+        let pending = self.pending.lock().await;
+        Ok(StoreStats {
+            pending_events: pending.len(),
+            pending_deliveries: pending.values().map(|p| p.targets.len()).sum(),
+            disk_bytes: 0,
+        })
+    }
+}
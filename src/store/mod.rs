@@ -0,0 +1,76 @@
+use crate::Error;
+use crate::Event;
+use crate::{StoreBackend, StoreConfig};
+use async_trait::async_trait;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) mod dead_letter;
+pub(crate) mod file;
+pub(crate) mod memory;
+pub(crate) mod queue;
+#[cfg(feature = "sqlite")]
+pub(crate) mod sqlite;
+
+/// An event that still has to be delivered to at least one target.
+#[derive(Debug, Clone)]
+pub struct PendingEvent {
+    pub event: Event,
+    pub targets: Vec<String>,
+}
+
+/// A snapshot of the contents of an event storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Number of events not yet delivered to all of their targets
+    pub pending_events: usize,
+    /// Number of outstanding deliveries across all pending events
+    pub pending_deliveries: usize,
+    /// Bytes occupied on disk, zero for in-memory storage
+    pub disk_bytes: u64,
+}
+
+/// The `EventStorage` trait defines the interface of the delivery ledger.
+///
+/// Events are appended before they are dispatched together with the targets they have to reach,
+/// and acknowledged per target once delivered. Whatever was not acknowledged is returned by
+/// `load_pending` so it can be replayed after a restart.
+#[async_trait]
+pub trait EventStorage: Send + Sync + 'static {
+    /// Records `event` as due for delivery to `targets` and returns the targets still pending.
+    ///
+    /// Appending an event that is already stored does not record it again; only the targets
+    /// that have not acknowledged it yet are returned.
+    async fn append(&self, event: &Event, targets: &[String]) -> Result<Vec<String>, Error>;
+    /// Records that the event `id` was delivered to `target`.
+    async fn ack(&self, id: Uuid, target: &str) -> Result<(), Error>;
+    /// Returns every event that has not been acknowledged by all of its targets, oldest first.
+    async fn load_pending(&self) -> Result<Vec<PendingEvent>, Error>;
+    /// Discards every pending event and returns how many were dropped.
+    async fn purge(&self) -> Result<usize, Error>;
+    /// Returns a snapshot of the storage contents.
+    async fn stats(&self) -> Result<StoreStats, Error>;
+}
+
+/// Creates the event storage selected by the provided configuration.
+///
+/// The file and SQLite backends keep their data in `path`.
+pub async fn create_storage(
+    path: &str,
+    config: StoreConfig,
+) -> Result<Arc<dyn EventStorage>, Error> {
+    config.validate().map_err(Error::ConfigError)?;
+    let storage: Arc<dyn EventStorage> = match config.backend {
+        StoreBackend::Memory => Arc::new(memory::MemoryStore::new()),
+        StoreBackend::File => Arc::new(file::FileStore::with_config(path, config).await?),
+        #[cfg(feature = "sqlite")]
+        StoreBackend::Sqlite => {
+            tokio::fs::create_dir_all(path).await?;
+            let db_path = std::path::Path::new(path).join(sqlite::DATABASE_FILE);
+            Arc::new(sqlite::SqliteStore::new(&db_path, config.fsync).await?)
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StoreBackend::Sqlite => return Err(Error::FeatureDisabled("sqlite")),
+    };
+    Ok(storage)
+}
@@ -0,0 +1,118 @@
+use crate::Error;
+use crate::Log;
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs::create_dir_all;
+use uuid::Uuid;
+
+const QUEUE_ENTRY_SUFFIX: &str = ".json";
+
+/// `QueueStore` is the persistent queue of a single adapter.
+///
+/// Each entry is one serialized [`Log`] stored in its own file inside the queue directory. Entries
+/// are written before delivery is attempted and removed once the adapter has accepted them, so
+/// events survive a crash or an unreachable target.
+pub struct QueueStore {
+    dir: PathBuf,
+    limit: u64,
+    len: AtomicU64,
+}
+
+impl QueueStore {
+    /// Opens the queue in `dir`, holding at most `limit` entries.
+    pub async fn new(dir: &str, limit: u64) -> Result<Self, Error> {
+        if limit == 0 {
+            return Err(Error::ConfigError(
+                "queue_limit must be greater than 0".to_string(),
+            ));
+        }
+        create_dir_all(dir).await?;
+        let store = Self {
+            dir: PathBuf::from(dir),
+            limit,
+            len: AtomicU64::new(0),
+        };
+        let len = store.list().await?.len() as u64;
+        store.len.store(len, Ordering::SeqCst);
+        Ok(store)
+    }
+
+    /// Writes `log` to the queue and returns the key of the new entry.
+    ///
+    /// The slot of the entry is reserved before it is written, so concurrent puts never exceed the
+    /// limit.
+    pub async fn put(&self, log: &Log) -> Result<String, Error> {
+        self.len
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                (len < self.limit).then_some(len + 1)
+            })
+            .map_err(|_| Error::QueueFull(self.dir.display().to_string()))?;
+        let written = self.write(log).await;
+        if written.is_err() {
+            self.len.fetch_sub(1, Ordering::SeqCst);
+        }
+        written
+    }
+
+    /// Writes `log` to a new entry file and returns its key.
+    async fn write(&self, log: &Log) -> Result<String, Error> {
+        let key = format!(
+            "{:020}-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            Uuid::new_v4()
+        );
+        let data = serde_json::to_vec(log)?;
+        // write to a temporary file first so that a partial entry is never picked up
+        let tmp_path = self.dir.join(format!("{key}.tmp"));
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, self.entry_path(&key)).await?;
+        Ok(key)
+    }
+
+    /// Reads the entry stored under `key`.
+    pub async fn get(&self, key: &str) -> Result<Log, Error> {
+        let data = tokio::fs::read(self.entry_path(key)).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Removes the entry stored under `key`.
+    pub async fn del(&self, key: &str) -> Result<(), Error> {
+        match tokio::fs::remove_file(self.entry_path(key)).await {
+            Ok(()) => {
+                self.len.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Lists the keys of all entries, oldest first.
+    pub async fn list(&self) -> Result<Vec<String>, Error> {
+        let mut keys = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            if let Some(key) = file_name.to_string_lossy().strip_suffix(QUEUE_ENTRY_SUFFIX) {
+                keys.push(key.to_string());
+            }
+        }
+        keys.sort_unstable();
+        Ok(keys)
+    }
+
+    /// Returns the number of entries in the queue.
+    pub fn len(&self) -> u64 {
+        self.len.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if the queue holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}{QUEUE_ENTRY_SUFFIX}"))
+    }
+}
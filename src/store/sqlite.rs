@@ -0,0 +1,200 @@
+use super::{EventStorage, PendingEvent, StoreStats};
+use crate::Error;
+use crate::Event;
+use crate::FsyncPolicy;
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Name of the database file created in the store path.
+pub(crate) const DATABASE_FILE: &str = "events.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deliveries (
+    event_id TEXT NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+    PRIMARY KEY (event_id, target)
+);
+";
+
+/// `SqliteStore` keeps the delivery ledger in an embedded SQLite database.
+///
+/// Events are stored as JSON in an `events` table and outstanding deliveries as one row per target
+/// in a `deliveries` table. The [`FsyncPolicy`] maps onto SQLite's `synchronous` setting.
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens or creates the database at `path`.
+    pub async fn new(path: &Path, fsync: FsyncPolicy) -> Result<Self, Error> {
+        let path = path.to_path_buf();
+        let conn = blocking(move || {
+            let conn = Connection::open(path)?;
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            conn.pragma_update(None, "foreign_keys", true)?;
+            let synchronous = match fsync {
+                FsyncPolicy::Always => "FULL",
+                FsyncPolicy::Interval => "NORMAL",
+                FsyncPolicy::Never => "OFF",
+            };
+            conn.pragma_update(None, "synchronous", synchronous)?;
+            conn.execute_batch(SCHEMA)?;
+            Ok(conn)
+        })
+        .await?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs `f` on the connection without blocking the async runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, Error> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| Error::custom("sqlite connection poisoned"))?;
+            f(&mut conn)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl EventStorage for SqliteStore {
+    async fn append(&self, event: &Event, targets: &[String]) -> Result<Vec<String>, Error> {
+        let event = event.clone();
+        let targets = targets.to_vec();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let id = event.id.to_string();
+            let known = tx
+                .query_row("SELECT 1 FROM events WHERE id = ?1", [&id], |_| Ok(()))
+                .optional()?
+                .is_some();
+            if known {
+                let mut stmt = tx.prepare("SELECT target FROM deliveries WHERE event_id = ?1")?;
+                let pending = stmt
+                    .query_map([&id], |row| row.get(0))?
+                    .collect::<Result<Vec<String>, _>>()?;
+                return Ok(pending);
+            }
+            if targets.is_empty() {
+                return Ok(targets);
+            }
+
+            tx.execute(
+                "INSERT INTO events (id, created_at, payload) VALUES (?1, ?2, ?3)",
+                params![
+                    id,
+                    event.timestamp.to_rfc3339(),
+                    serde_json::to_string(&event)?
+                ],
+            )?;
+            for target in &targets {
+                tx.execute(
+                    "INSERT OR IGNORE INTO deliveries (event_id, target) VALUES (?1, ?2)",
+                    params![id, target],
+                )?;
+            }
+            tx.commit()?;
+            Ok(targets)
+        })
+        .await
+    }
+
+    async fn ack(&self, id: Uuid, target: &str) -> Result<(), Error> {
+        let target = target.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let id = id.to_string();
+            tx.execute(
+                "DELETE FROM deliveries WHERE event_id = ?1 AND target = ?2",
+                params![id, target],
+            )?;
+            tx.execute(
+                "DELETE FROM events WHERE id = ?1 \
+                 AND NOT EXISTS (SELECT 1 FROM deliveries WHERE event_id = ?1)",
+                [&id],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load_pending(&self) -> Result<Vec<PendingEvent>, Error> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.payload, d.target FROM events e \
+                 JOIN deliveries d ON d.event_id = e.id \
+                 ORDER BY e.rowid",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, String)>, _>>()?;
+
+            let mut pending: Vec<PendingEvent> = Vec::new();
+            for (payload, target) in rows {
+                let event: Event = serde_json::from_str(&payload)?;
+                match pending.last_mut() {
+                    Some(last) if last.event.id == event.id => last.targets.push(target),
+                    _ => pending.push(PendingEvent {
+                        event,
+                        targets: vec![target],
+                    }),
+                }
+            }
+            Ok(pending)
+        })
+        .await
+    }
+
+    async fn purge(&self) -> Result<usize, Error> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM deliveries", [])?;
+            let purged = tx.execute("DELETE FROM events", [])?;
+            tx.commit()?;
+            Ok(purged)
+        })
+        .await
+    }
+
+    async fn stats(&self) -> Result<StoreStats, Error> {
+        self.with_conn(|conn| {
+            let pending_events: i64 =
+                conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
+            let pending_deliveries: i64 =
+                conn.query_row("SELECT COUNT(*) FROM deliveries", [], |row| row.get(0))?;
+            let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+            let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+            Ok(StoreStats {
+                pending_events: pending_events as usize,
+                pending_deliveries: pending_deliveries as usize,
+                disk_bytes: (page_count * page_size) as u64,
+            })
+        })
+        .await
+    }
+}
+
+async fn blocking<T, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await?
+}
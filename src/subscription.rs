@@ -0,0 +1,190 @@
+use crate::Error;
+use crate::{Event, Name, NameSet};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use tokio::sync::broadcast;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+
+/// What a subscription does when it falls more than `subscriber_capacity` events behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LagPolicy {
+    /// skip the missed events and carry on with the oldest one still available
+    #[default]
+    Skip,
+    /// end the stream
+    Close,
+}
+
+/// Selects the events a subscription receives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionFilter {
+    /// Event names to receive, every event by default
+    #[serde(default = "NameSet::all")]
+    pub events: NameSet,
+    #[serde(default)]
+    pub bucket: Option<String>,
+    /// Prefix of the object keys to receive
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Suffix of the object keys to receive
+    #[serde(default)]
+    pub suffix: Option<String>,
+    #[serde(default)]
+    pub lag: LagPolicy,
+}
+
+impl Default for SubscriptionFilter {
+    fn default() -> Self {
+        Self {
+            events: NameSet::all(),
+            bucket: None,
+            prefix: None,
+            suffix: None,
+            lag: LagPolicy::default(),
+        }
+    }
+}
+
+impl SubscriptionFilter {
+    /// Creates a filter letting every event through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only lets events of the given names through, wildcards such as `ObjectCreatedAll` included.
+    pub fn events(mut self, events: impl IntoIterator<Item = Name>) -> Self {
+        self.events = events.into_iter().collect();
+        self
+    }
+
+    /// only let events of the bucket through
+    pub fn bucket(mut self, bucket: impl Into<String>) -> Self {
+        self.bucket = Some(bucket.into());
+        self
+    }
+
+    /// only let object keys starting with the prefix through
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// only let object keys ending with the suffix through
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// set what happens when the subscription falls behind
+    pub fn lag(mut self, lag: LagPolicy) -> Self {
+        self.lag = lag;
+        self
+    }
+
+    /// Parses a filter from a URL query such as
+    /// `bucket=photos&prefix=2024/&suffix=.jpg&events=s3:ObjectCreated:*,s3:ObjectRemoved:*`.
+    ///
+    /// `events` may be repeated or hold a comma-separated list of names in either form, other
+    /// parameters are ignored.
+    pub fn from_query(query: &str) -> Result<Self, Error> {
+        let mut filter = Self::new();
+        let mut events = NameSet::new();
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "bucket" => filter.bucket = Some(value.into_owned()),
+                "prefix" => filter.prefix = Some(value.into_owned()),
+                "suffix" => filter.suffix = Some(value.into_owned()),
+                "events" => {
+                    for name in value.split(',').filter(|name| !name.is_empty()) {
+                        events.insert(Name::parse(name)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !events.is_empty() {
+            filter.events = events;
+        }
+        Ok(filter)
+    }
+
+    /// Determine whether `event` passes the filter.
+    pub fn matches(&self, event: &Event) -> bool {
+        let key = &event.s3.object.key;
+        self.events.contains(event.event_name)
+            && self
+                .bucket
+                .as_ref()
+                .is_none_or(|bucket| *bucket == event.s3.bucket.name)
+            && self
+                .prefix
+                .as_ref()
+                .is_none_or(|prefix| key.starts_with(prefix.as_str()))
+            && self
+                .suffix
+                .as_ref()
+                .is_none_or(|suffix| key.ends_with(suffix.as_str()))
+    }
+}
+
+/// A stream of the events sent to a notification system that pass a [`SubscriptionFilter`].
+///
+/// Events are broadcast to every subscription as they are handed to the event bus, whether or
+/// not an adapter receives them. A subscription that falls behind by more than
+/// `subscriber_capacity` events misses events as its [`LagPolicy`] says.
+pub struct Subscription {
+    inner: BroadcastStream<Arc<Event>>,
+    filter: SubscriptionFilter,
+    missed: u64,
+    closed: bool,
+}
+
+impl Subscription {
+    pub(crate) fn new(rx: broadcast::Receiver<Arc<Event>>, filter: SubscriptionFilter) -> Self {
+        Self {
+            inner: BroadcastStream::new(rx),
+            filter,
+            missed: 0,
+            closed: false,
+        }
+    }
+
+    /// Returns the number of events missed because the subscription fell behind.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        let this = &mut *self;
+        if this.closed {
+            return Poll::Ready(None);
+        }
+        loop {
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(event)) => {
+                    if this.filter.matches(&event) {
+                        return Poll::Ready(Some(Arc::unwrap_or_clone(event)));
+                    }
+                }
+                Some(Err(BroadcastStreamRecvError::Lagged(missed))) => {
+                    this.missed += missed;
+                    tracing::warn!("Subscription fell behind, missed {} events", missed);
+                    if this.filter.lag == LagPolicy::Close {
+                        this.closed = true;
+                        return Poll::Ready(None);
+                    }
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{AdapterConfig, Error, Name, NotificationRule, TargetArn};
+use serde::{Deserialize, Serialize};
+
+/// Namespace of the S3 API documents.
+const S3_XMLNS: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+
+/// An S3 `NotificationConfiguration` document, as sent with `PutBucketNotificationConfiguration`.
+///
+/// Queue, topic and cloud function configurations are kept apart so a document round-trips
+/// unchanged, the crate delivers to all of them the same way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationConfiguration {
+    pub queues: Vec<TargetConfiguration>,
+    pub topics: Vec<TargetConfiguration>,
+    pub cloud_functions: Vec<TargetConfiguration>,
+}
+
+/// One `QueueConfiguration`, `TopicConfiguration` or `CloudFunctionConfiguration` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetConfiguration {
+    pub id: Option<String>,
+    pub arn: TargetArn,
+    pub events: Vec<Name>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+}
+
+impl NotificationConfiguration {
+    /// Parses a `NotificationConfiguration` document.
+    ///
+    /// Event names must be canonical S3 event types such as `s3:ObjectCreated:*`, filter rules
+    /// may only be `prefix` or `suffix`.
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        let document: XmlNotificationConfiguration =
+            quick_xml::de::from_str(xml).map_err(|e| Error::Xml(e.to_string()))?;
+        let parse = |elements: Vec<XmlTargetConfiguration>| {
+            elements
+                .into_iter()
+                .map(TargetConfiguration::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            queues: parse(document.queues)?,
+            topics: parse(document.topics)?,
+            cloud_functions: parse(document.cloud_functions)?,
+        })
+    }
+
+    /// Serializes the configuration as a `NotificationConfiguration` document.
+    pub fn to_xml(&self) -> Result<String, Error> {
+        let document = XmlNotificationConfiguration {
+            xmlns: Some(S3_XMLNS.to_string()),
+            queues: self.queues.iter().map(|c| c.to_xml(Kind::Queue)).collect(),
+            topics: self.topics.iter().map(|c| c.to_xml(Kind::Topic)).collect(),
+            cloud_functions: self
+                .cloud_functions
+                .iter()
+                .map(|c| c.to_xml(Kind::CloudFunction))
+                .collect(),
+        };
+        quick_xml::se::to_string(&document).map_err(|e| Error::Xml(e.to_string()))
+    }
+
+    /// Returns every configuration of the document.
+    pub fn configurations(&self) -> impl Iterator<Item = &TargetConfiguration> {
+        self.queues
+            .iter()
+            .chain(&self.topics)
+            .chain(&self.cloud_functions)
+    }
+
+    /// Converts the configuration of `bucket` into notification rules targeting `adapters`.
+    ///
+    /// Returns [`Error::UnknownTarget`] when an ARN designates none of the adapters.
+    pub fn to_rules(
+        &self,
+        bucket: &str,
+        adapters: &[AdapterConfig],
+    ) -> Result<Vec<NotificationRule>, Error> {
+        self.configurations()
+            .map(|configuration| {
+                let adapter = adapters
+                    .iter()
+                    .find(|adapter| adapter.is_target_of(&configuration.arn))
+                    .ok_or_else(|| Error::UnknownTarget(configuration.arn.to_string()))?;
+                let rule = NotificationRule {
+                    id: configuration.id.clone(),
+                    bucket: Some(bucket.to_string()),
+                    events: configuration.events.clone(),
+                    prefix: configuration.prefix.clone(),
+                    suffix: configuration.suffix.clone(),
+                    targets: vec![adapter.id()],
+                };
+                rule.validate().map_err(Error::ConfigError)?;
+                Ok(rule)
+            })
+            .collect()
+    }
+
+    /// Builds the configuration of `bucket` from the rules applying to it.
+    ///
+    /// Every target of a rule becomes a queue configuration, targets are named by the ARN of their
+    /// adapter in `region`. Returns [`Error::UnknownTarget`] when a target is none of the adapters.
+    pub fn from_rules(
+        rules: &[NotificationRule],
+        bucket: &str,
+        adapters: &[AdapterConfig],
+        region: &str,
+    ) -> Result<Self, Error> {
+        let mut queues = Vec::new();
+        for rule in rules
+            .iter()
+            .filter(|rule| rule.bucket.as_ref().is_none_or(|b| b == bucket))
+        {
+            for target in &rule.targets {
+                let adapter = adapters
+                    .iter()
+                    .find(|adapter| adapter.id() == *target)
+                    .ok_or_else(|| Error::UnknownTarget(target.clone()))?;
+                queues.push(TargetConfiguration {
+                    id: rule.id.clone(),
+                    arn: adapter.arn(region),
+                    events: rule.events.clone(),
+                    prefix: rule.prefix.clone(),
+                    suffix: rule.suffix.clone(),
+                });
+            }
+        }
+        Ok(Self {
+            queues,
+            ..Default::default()
+        })
+    }
+}
+
+/// The element holding the ARN of a configuration.
+#[derive(Clone, Copy)]
+enum Kind {
+    Queue,
+    Topic,
+    CloudFunction,
+}
+
+impl TargetConfiguration {
+    fn to_xml(&self, kind: Kind) -> XmlTargetConfiguration {
+        let arn = Some(self.arn.to_string());
+        let mut rules = Vec::new();
+        if let Some(prefix) = &self.prefix {
+            rules.push(XmlFilterRule {
+                name: "prefix".to_string(),
+                value: prefix.clone(),
+            });
+        }
+        if let Some(suffix) = &self.suffix {
+            rules.push(XmlFilterRule {
+                name: "suffix".to_string(),
+                value: suffix.clone(),
+            });
+        }
+        XmlTargetConfiguration {
+            id: self.id.clone(),
+            filter: (!rules.is_empty()).then_some(XmlFilter {
+                key: XmlS3Key { rules },
+            }),
+            queue: arn.clone().filter(|_| matches!(kind, Kind::Queue)),
+            topic: arn.clone().filter(|_| matches!(kind, Kind::Topic)),
+            cloud_function: arn.filter(|_| matches!(kind, Kind::CloudFunction)),
+            events: self
+                .events
+                .iter()
+                .map(|name| name.as_s3_str().to_string())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<XmlTargetConfiguration> for TargetConfiguration {
+    type Error = Error;
+
+    fn try_from(element: XmlTargetConfiguration) -> Result<Self, Error> {
+        let arn = element
+            .queue
+            .or(element.topic)
+            .or(element.cloud_function)
+            .ok_or_else(|| Error::Xml("configuration is missing its target ARN".to_string()))?;
+        let arn = arn
+            .trim()
+            .parse()
+            .map_err(|_| Error::UnknownTarget(arn.clone()))?;
+        let events = element
+            .events
+            .iter()
+            .map(|event| Name::from_s3_str(event.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut prefix = None;
+        let mut suffix = None;
+        for rule in element.filter.map(|f| f.key.rules).unwrap_or_default() {
+            let slot = match rule.name.to_ascii_lowercase().as_str() {
+                "prefix" => &mut prefix,
+                "suffix" => &mut suffix,
+                _ => return Err(Error::InvalidFilterRule(rule.name)),
+            };
+            if slot.replace(rule.value).is_some() {
+                return Err(Error::InvalidFilterRule(format!(
+                    "duplicate {} rule",
+                    rule.name
+                )));
+            }
+        }
+
+        Ok(Self {
+            id: element.id,
+            arn,
+            events,
+            prefix,
+            suffix,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename = "NotificationConfiguration")]
+struct XmlNotificationConfiguration {
+    #[serde(rename = "@xmlns", default, skip_serializing_if = "Option::is_none")]
+    xmlns: Option<String>,
+    #[serde(rename = "QueueConfiguration", default)]
+    queues: Vec<XmlTargetConfiguration>,
+    #[serde(rename = "TopicConfiguration", default)]
+    topics: Vec<XmlTargetConfiguration>,
+    #[serde(rename = "CloudFunctionConfiguration", default)]
+    cloud_functions: Vec<XmlTargetConfiguration>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct XmlTargetConfiguration {
+    #[serde(rename = "Id", default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "Filter", default, skip_serializing_if = "Option::is_none")]
+    filter: Option<XmlFilter>,
+    #[serde(rename = "Queue", default, skip_serializing_if = "Option::is_none")]
+    queue: Option<String>,
+    #[serde(rename = "Topic", default, skip_serializing_if = "Option::is_none")]
+    topic: Option<String>,
+    #[serde(
+        rename = "CloudFunction",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    cloud_function: Option<String>,
+    #[serde(rename = "Event", default)]
+    events: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct XmlFilter {
+    #[serde(rename = "S3Key", default)]
+    key: XmlS3Key,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct XmlS3Key {
+    #[serde(rename = "FilterRule", default)]
+    rules: Vec<XmlFilterRule>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct XmlFilterRule {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Value")]
+    value: String,
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use common::{RecordingAdapter, temp_store_path, test_event, wait_until};
+use event_notification::{
+    ChannelAdapter, CircuitBreaker, CircuitBreakerConfig, CircuitState, DeadLetterStore, Error,
+    EventStorage, MemoryStore, RetryPolicy, RulesMap, TargetConfig,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_circuit_breaker() {
+    let adapter = RecordingAdapter::new("webhook");
+    let breaker = CircuitBreaker::new(
+        adapter.clone(),
+        &CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_timeout_ms: 50,
+        },
+    );
+
+    adapter.set_failing(true);
+    for _ in 0..2 {
+        assert!(matches!(
+            breaker.send(&test_event("a")).await,
+            Err(Error::Io(_))
+        ));
+    }
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert_eq!(breaker.consecutive_failures(), 2);
+
+    // rejected without reaching the adapter even once it recovered
+    adapter.set_failing(false);
+    assert!(matches!(
+        breaker.send(&test_event("b")).await,
+        Err(Error::CircuitOpen(_))
+    ));
+    assert!(adapter.keys().is_empty());
+
+    // a failing probe opens the circuit again
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    adapter.set_failing(true);
+    assert!(breaker.send(&test_event("c")).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // a successful probe closes it
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    adapter.set_failing(false);
+    breaker.send(&test_event("d")).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.consecutive_failures(), 0);
+    assert_eq!(adapter.keys(), vec!["d"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_open_circuit_holds_deliveries() {
+    let flaky = RecordingAdapter::new("flaky");
+    let breaker = Arc::new(CircuitBreaker::new(
+        flaky.clone(),
+        &CircuitBreakerConfig {
+            failure_threshold: 1,
+            open_timeout_ms: 1000,
+        },
+    ));
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![breaker.clone()];
+    let targets = HashMap::from([(
+        "flaky".to_string(),
+        TargetConfig {
+            retry: Some(RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )]);
+    let dead_letters = Arc::new(DeadLetterStore::new(&temp_store_path()).await.unwrap());
+    let store = Arc::new(MemoryStore::new());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        targets,
+        Arc::new(RulesMap::default()),
+        Some(dead_letters.clone()),
+        shutdown.clone(),
+    ));
+
+    // the failure opening the circuit is dead-lettered
+    flaky.set_failing(true);
+    let mut event = test_event("a");
+    event.channels = vec!["flaky".to_string()].into();
+    tx.send(event).await.unwrap();
+    wait_until(async || dead_letters.list().await.unwrap().len() == 1).await;
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // events rejected by the open circuit stay pending
+    let mut event = test_event("b");
+    event.channels = vec!["flaky".to_string()].into();
+    tx.send(event).await.unwrap();
+    wait_until(async || store.load_pending().await.unwrap().len() == 1).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(dead_letters.list().await.unwrap().len(), 1);
+    assert_eq!(
+        store.load_pending().await.unwrap()[0].event.s3.object.key,
+        "b"
+    );
+
+    // and are delivered once the circuit lets them through
+    flaky.set_failing(false);
+    wait_until(async || flaky.keys() == vec!["b"]).await;
+    wait_until(async || store.load_pending().await.unwrap().is_empty()).await;
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(dead_letters.list().await.unwrap().len(), 1);
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_open_circuit_does_not_hold_up_other_targets() {
+    let flaky = RecordingAdapter::new("flaky");
+    let healthy = RecordingAdapter::new("healthy");
+    let breaker = Arc::new(CircuitBreaker::new(
+        flaky.clone(),
+        &CircuitBreakerConfig {
+            failure_threshold: 1,
+            open_timeout_ms: 1000,
+        },
+    ));
+    flaky.set_failing(true);
+    assert!(breaker.send(&test_event("opening")).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![breaker.clone(), healthy.clone()];
+    let config = TargetConfig {
+        channel_capacity: 2,
+        ..Default::default()
+    };
+    let targets = HashMap::from([
+        ("flaky".to_string(), config.clone()),
+        ("healthy".to_string(), config),
+    ]);
+    let store = Arc::new(MemoryStore::new());
+    let (tx, rx) = mpsc::channel(2);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        targets,
+        Arc::new(RulesMap::default()),
+        None,
+        shutdown.clone(),
+    ));
+
+    // far more events than the channel of the unavailable target holds
+    let keys: Vec<String> = (0..20).map(|i| format!("{i:02}")).collect();
+    for key in &keys {
+        let mut event = test_event(key);
+        event.channels = vec!["flaky".to_string(), "healthy".to_string()].into();
+        tx.send(event).await.unwrap();
+    }
+    wait_until(async || healthy.keys().len() == keys.len()).await;
+    assert_eq!(healthy.keys(), keys);
+    assert!(flaky.keys().is_empty());
+    assert_eq!(store.load_pending().await.unwrap().len(), keys.len());
+
+    // the parked events are delivered once the target recovers
+    flaky.set_failing(false);
+    wait_until(async || flaky.keys().len() == keys.len()).await;
+    let mut delivered = flaky.keys();
+    delivered.sort();
+    assert_eq!(delivered, keys);
+    wait_until(async || store.load_pending().await.unwrap().is_empty()).await;
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use common::{RecordingAdapter, temp_store_path, test_event, wait_until};
+use event_notification::{
+    BatchConfig, ChannelAdapter, DeadLetterStore, EventStorage, MemoryStore, Name,
+    NotificationRule, RetryPolicy, RulesMap, TargetConfig,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_target_does_not_block_others() {
+    let slow = RecordingAdapter::with_delay("slow", Duration::from_millis(300));
+    let pooled = RecordingAdapter::with_delay("pooled", Duration::from_millis(300));
+    let fast = RecordingAdapter::new("fast");
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![slow.clone(), pooled.clone(), fast.clone()];
+    let rules = RulesMap::new(vec![NotificationRule {
+        id: None,
+        bucket: None,
+        events: vec![Name::Everything],
+        prefix: None,
+        suffix: None,
+        targets: vec!["slow".to_string(), "pooled".to_string(), "fast".to_string()],
+    }]);
+    let targets = HashMap::from([(
+        "pooled".to_string(),
+        TargetConfig {
+            concurrency: 3,
+            ..Default::default()
+        },
+    )]);
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        Arc::new(MemoryStore::new()),
+        targets,
+        Arc::new(rules),
+        None,
+        shutdown.clone(),
+    ));
+
+    for key in ["a", "b", "c"] {
+        tx.send(test_event(key)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(fast.keys(), vec!["a", "b", "c"]);
+    assert!(slow.keys().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(slow.keys(), vec!["a"]);
+    let mut pooled_keys = pooled.keys();
+    pooled_keys.sort();
+    assert_eq!(pooled_keys, vec!["a", "b", "c"]);
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batched_delivery() {
+    let adapter = RecordingAdapter::new("batched");
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![adapter.clone()];
+    let rules = RulesMap::new(vec![NotificationRule {
+        id: None,
+        bucket: None,
+        events: vec![Name::Everything],
+        prefix: None,
+        suffix: None,
+        targets: vec!["batched".to_string()],
+    }]);
+    let targets = HashMap::from([(
+        "batched".to_string(),
+        TargetConfig {
+            batch: Some(BatchConfig {
+                max_events: 3,
+                linger_ms: 100,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )]);
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        Arc::new(MemoryStore::new()),
+        targets,
+        Arc::new(rules),
+        None,
+        shutdown.clone(),
+    ));
+
+    // full batches go out right away, the remainder once the linger time has elapsed
+    for key in ["a", "b", "c", "d", "e", "f", "g"] {
+        tx.send(test_event(key)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(*adapter.batches.lock().unwrap(), vec![3, 3]);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(adapter.keys(), vec!["a", "b", "c", "d", "e", "f", "g"]);
+    assert_eq!(*adapter.batches.lock().unwrap(), vec![3, 3]);
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_full_queue_holds_up_the_bus() {
+    let flaky = RecordingAdapter::new("flaky");
+    flaky.set_failing(true);
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![flaky.clone()];
+    let queue_dir = temp_store_path();
+    let targets = HashMap::from([(
+        "flaky".to_string(),
+        TargetConfig {
+            queue_dir: Some(queue_dir.clone()),
+            queue_limit: 1,
+            ..Default::default()
+        },
+    )]);
+    let store = Arc::new(MemoryStore::new());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        targets,
+        Arc::new(RulesMap::default()),
+        None,
+        shutdown.clone(),
+    ));
+
+    for key in ["a", "b"] {
+        let mut event = test_event(key);
+        event.channels = vec!["flaky".to_string()].into();
+        tx.send(event).await.unwrap();
+    }
+
+    // the second event waits for room in the queue instead of being dropped
+    wait_until(async || store.load_pending().await.unwrap().len() == 1).await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let pending = store.load_pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event.s3.object.key, "b");
+
+    flaky.set_failing(false);
+    wait_until(async || flaky.keys() == vec!["a", "b"]).await;
+    assert!(store.load_pending().await.unwrap().is_empty());
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+    let _ = tokio::fs::remove_dir_all(&queue_dir).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_partially_delivered_queue_batches() {
+    let adapter = RecordingAdapter::new("batched");
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![adapter.clone()];
+    let queue_dir = temp_store_path();
+    let targets = HashMap::from([(
+        "batched".to_string(),
+        TargetConfig {
+            queue_dir: Some(queue_dir.clone()),
+            retry: Some(RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            }),
+            batch: Some(BatchConfig {
+                max_events: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )]);
+    let dead_letters = Arc::new(DeadLetterStore::new(&temp_store_path()).await.unwrap());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        Arc::new(MemoryStore::new()),
+        targets,
+        Arc::new(RulesMap::default()),
+        Some(dead_letters.clone()),
+        shutdown.clone(),
+    ));
+    // queues the events while the target is unreachable, so they are drained as one batch
+    let queued = || std::fs::read_dir(&queue_dir).map_or(0, |entries| entries.count());
+    let queue = async |keys: &[&str]| {
+        adapter.set_failing(true);
+        for key in keys {
+            let mut event = test_event(key);
+            event.channels = vec!["batched".to_string()].into();
+            tx.send(event).await.unwrap();
+        }
+        wait_until(async || queued() == keys.len()).await;
+        adapter.set_failing(false);
+    };
+
+    // only the event that was not delivered is dead-lettered
+    adapter.set_rejected_keys(&["b"]);
+    queue(&["a", "b", "c"]).await;
+    wait_until(async || dead_letters.list().await.unwrap().len() == 1).await;
+    assert_eq!(
+        dead_letters.list().await.unwrap()[0].event.s3.object.key,
+        "b"
+    );
+    assert_eq!(adapter.keys(), vec!["a", "c"]);
+
+    // and only the event that ran out of retries is sent again
+    adapter.set_rejected_keys(&[]);
+    adapter.set_failing_keys(&["e"]);
+    queue(&["d", "e", "f"]).await;
+    wait_until(async || adapter.keys().len() == 4).await;
+    assert_eq!(adapter.keys(), vec!["a", "c", "d", "f"]);
+    wait_until(async || queued() == 1).await;
+    adapter.set_failing_keys(&[]);
+    wait_until(async || adapter.keys().len() == 5).await;
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(adapter.keys(), vec!["a", "c", "d", "f", "e"]);
+    assert_eq!(*adapter.batches.lock().unwrap(), vec![3, 3]);
+    assert_eq!(dead_letters.list().await.unwrap().len(), 1);
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+    let _ = tokio::fs::remove_dir_all(&queue_dir).await;
+}
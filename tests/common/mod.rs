@@ -0,0 +1,182 @@
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use event_notification::{Bucket, Event, Identity, Metadata, Name, Object, Source};
+use event_notification::{ChannelAdapter, Error};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Creates an `ObjectCreatedPut` event for `key` in `my-bucket`.
+pub fn test_event(key: &str) -> Event {
+    test_event_in(Name::ObjectCreatedPut, "my-bucket", key)
+}
+
+/// Creates an event of type `name` for `key` in `bucket`.
+pub fn test_event_in(name: Name, bucket: &str, key: &str) -> Event {
+    let metadata = Metadata {
+        schema_version: "1.0".to_string(),
+        configuration_id: "test-config".to_string(),
+        bucket: Bucket {
+            name: bucket.to_string(),
+            owner_identity: Identity {
+                principal_id: "owner123".to_string(),
+            },
+            arn: format!("arn:aws:s3:::{bucket}"),
+        },
+        object: Object {
+            key: key.to_string(),
+            size: Some(1024),
+            etag: None,
+            content_type: None,
+            user_metadata: None,
+            version_id: None,
+            sequencer: "1234567890".to_string(),
+        },
+    };
+    let source = Source {
+        host: "localhost".to_string(),
+        port: "80".to_string(),
+        user_agent: "curl/7.68.0".to_string(),
+    };
+    Event::create(name, metadata, source, Vec::new())
+}
+
+/// Returns a fresh directory path under the system temp dir.
+pub fn temp_store_path() -> String {
+    std::env::temp_dir()
+        .join(format!("event-notification-{}", uuid::Uuid::new_v4()))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Polls `condition` until it holds, failing the test if it does not within ten seconds.
+pub async fn wait_until(mut condition: impl AsyncFnMut() -> bool) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while !condition().await {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "condition not met in time"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// An adapter that records every event it is sent.
+pub struct RecordingAdapter {
+    id: String,
+    delay: Duration,
+    failing: AtomicBool,
+    /// Object keys of the events that fail to send
+    failing_keys: Mutex<Vec<String>>,
+    /// Object keys of the events rejected with an error that is not retryable
+    rejected_keys: Mutex<Vec<String>>,
+    pub sent: Arc<Mutex<Vec<Event>>>,
+    /// Sizes of the batches sent through `send_batch`
+    pub batches: Arc<Mutex<Vec<usize>>>,
+}
+
+impl RecordingAdapter {
+    pub fn new(id: &str) -> Arc<Self> {
+        Self::with_delay(id, Duration::ZERO)
+    }
+
+    /// Creates an adapter that takes `delay` to send each event.
+    pub fn with_delay(id: &str, delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            id: id.to_string(),
+            delay,
+            failing: AtomicBool::new(false),
+            failing_keys: Mutex::new(Vec::new()),
+            rejected_keys: Mutex::new(Vec::new()),
+            sent: Arc::new(Mutex::new(Vec::new())),
+            batches: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// Makes every following send fail, or succeed again.
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::SeqCst);
+    }
+
+    /// Makes the events for `keys` fail to send, the others being sent.
+    pub fn set_failing_keys(&self, keys: &[&str]) {
+        *self.failing_keys.lock().unwrap() = keys.iter().map(|key| key.to_string()).collect();
+    }
+
+    /// Makes the events for `keys` fail with an error that is not retried.
+    pub fn set_rejected_keys(&self, keys: &[&str]) {
+        *self.rejected_keys.lock().unwrap() = keys.iter().map(|key| key.to_string()).collect();
+    }
+
+    /// Returns the error sending `event` fails with, if any.
+    fn failure(&self, event: &Event) -> Option<Error> {
+        let key = &event.s3.object.key;
+        if self.failing.load(Ordering::SeqCst) {
+            Some(Error::Io(Box::new(std::io::Error::other(
+                "target unreachable",
+            ))))
+        } else if self.failing_keys.lock().unwrap().contains(key) {
+            Some(Error::Io(Box::new(std::io::Error::other("event rejected"))))
+        } else if self.rejected_keys.lock().unwrap().contains(key) {
+            Some(Error::Custom("event refused".to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the object keys of the events sent so far.
+    pub fn keys(&self) -> Vec<String> {
+        let sent = self.sent.lock().unwrap();
+        sent.iter().map(|e| e.s3.object.key.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl ChannelAdapter for RecordingAdapter {
+    fn name(&self) -> String {
+        "recording".to_string()
+    }
+
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    async fn is_active(&self) -> Result<bool, Error> {
+        Ok(!self.failing.load(Ordering::SeqCst))
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), Error> {
+        tokio::time::sleep(self.delay).await;
+        if let Some(e) = self.failure(event) {
+            return Err(e);
+        }
+        self.sent.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+
+    async fn send_batch(&self, events: &[Event]) -> Result<(), Error> {
+        tokio::time::sleep(self.delay).await;
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(Error::Io(Box::new(std::io::Error::other(
+                "target unreachable",
+            ))));
+        }
+        self.batches.lock().unwrap().push(events.len());
+        let mut failed = Vec::new();
+        let mut error = None;
+        for (i, event) in events.iter().enumerate() {
+            match self.failure(event) {
+                Some(e) => {
+                    failed.push(i);
+                    error.get_or_insert(e);
+                }
+                None => self.sent.lock().unwrap().push(event.clone()),
+            }
+        }
+        match error {
+            None => Ok(()),
+            Some(e) => Err(Error::PartialBatch(failed, Box::new(e))),
+        }
+    }
+}
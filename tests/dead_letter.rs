@@ -0,0 +1,144 @@
+mod common;
+
+use common::{RecordingAdapter, temp_store_path, test_event, wait_until};
+use event_notification::{
+    BatchConfig, ChannelAdapter, DeadLetterStore, Error, EventStorage, MemoryStore, RetryPolicy,
+    RulesMap, TargetConfig,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_dead_letters_are_recorded_and_requeued() {
+    let flaky = RecordingAdapter::new("flaky");
+    flaky.set_failing(true);
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![flaky.clone()];
+    let targets = HashMap::from([(
+        "flaky".to_string(),
+        TargetConfig {
+            retry: Some(RetryPolicy {
+                max_attempts: 2,
+                base_delay_ms: 1,
+                max_delay_ms: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )]);
+    let dead_letters = Arc::new(DeadLetterStore::new(&temp_store_path()).await.unwrap());
+    let store = Arc::new(MemoryStore::new());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        targets,
+        Arc::new(RulesMap::default()),
+        Some(dead_letters.clone()),
+        shutdown.clone(),
+    ));
+
+    let mut event = test_event("a.txt");
+    event.channels = vec!["flaky".to_string()].into();
+    tx.send(event.clone()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let letters = dead_letters.list().await.unwrap();
+    assert_eq!(letters.len(), 1);
+    let letter = &letters[0];
+    assert_eq!(letter.target, "flaky");
+    assert_eq!(letter.event.id, event.id);
+    assert_eq!(letter.attempts, 2);
+    assert!(letter.error.contains("target unreachable"));
+    // the dead letter takes over from the ledger
+    assert!(store.load_pending().await.unwrap().is_empty());
+
+    // a failed requeue keeps the dead letter
+    dead_letters.requeue(letter.id).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let letters = dead_letters.list().await.unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].id, letter.id);
+
+    flaky.set_failing(false);
+    dead_letters.requeue(letter.id).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(flaky.keys(), vec!["a.txt"]);
+    assert!(dead_letters.list().await.unwrap().is_empty());
+    assert!(matches!(
+        dead_letters.requeue(letter.id).await,
+        Err(Error::DeadLetterNotFound(id)) if id == letter.id
+    ));
+
+    flaky.set_failing(true);
+    tx.send(test_event("b.txt")).await.unwrap();
+    let mut event = test_event("c.txt");
+    event.channels = vec!["flaky".to_string()].into();
+    tx.send(event).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(dead_letters.purge().await.unwrap(), 1);
+    assert!(dead_letters.list().await.unwrap().is_empty());
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_only_failed_events_of_a_batch_are_retried() {
+    let adapter = RecordingAdapter::new("batched");
+    adapter.set_failing_keys(&["b.txt"]);
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![adapter.clone()];
+    let targets = HashMap::from([(
+        "batched".to_string(),
+        TargetConfig {
+            retry: Some(RetryPolicy {
+                max_attempts: 3,
+                base_delay_ms: 1,
+                max_delay_ms: 1,
+                ..Default::default()
+            }),
+            batch: Some(BatchConfig {
+                max_events: 3,
+                linger_ms: 10,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )]);
+    let dead_letters = Arc::new(DeadLetterStore::new(&temp_store_path()).await.unwrap());
+    let store = Arc::new(MemoryStore::new());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        targets,
+        Arc::new(RulesMap::default()),
+        Some(dead_letters.clone()),
+        shutdown.clone(),
+    ));
+
+    for key in ["a.txt", "b.txt", "c.txt"] {
+        let mut event = test_event(key);
+        event.channels = vec!["batched".to_string()].into();
+        tx.send(event).await.unwrap();
+    }
+    wait_until(async || dead_letters.list().await.unwrap().len() == 1).await;
+
+    // the delivered events are not sent again
+    assert_eq!(adapter.keys(), vec!["a.txt", "c.txt"]);
+    let letters = dead_letters.list().await.unwrap();
+    assert_eq!(letters[0].event.s3.object.key, "b.txt");
+    assert_eq!(letters[0].attempts, 3);
+    assert!(store.load_pending().await.unwrap().is_empty());
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::test_event_in;
+use event_notification::{Error, Event, Name, NameFormat, PayloadFormat};
+use serde_json::Value;
+
+#[test]
+fn test_s3_payload_format() {
+    let event = test_event_in(Name::ObjectCreatedPut, "photos", "images/red flower.jpg");
+
+    let payload: Value = serde_json::from_str(
+        &event
+            .to_payload(PayloadFormat::S3, NameFormat::Constant)
+            .unwrap(),
+    )
+    .unwrap();
+    let records = payload["Records"].as_array().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record["eventName"], "s3:ObjectCreated:Put");
+    assert_eq!(record["s3"]["bucket"]["name"], "photos");
+    assert_eq!(record["s3"]["object"]["key"], "images%2Fred+flower.jpg");
+    assert!(record["responseElements"]["x-amz-request-id"].is_string());
+    for field in ["id", "timestamp", "channels", "source"] {
+        assert!(record.get(field).is_none(), "unexpected field {field}");
+    }
+
+    let native: Value = serde_json::from_str(
+        &event
+            .to_payload(PayloadFormat::Native, NameFormat::Constant)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(native["eventName"], Name::ObjectCreatedPut.to_string());
+    assert_eq!(native["s3"]["object"]["key"], "images/red flower.jpg");
+    assert_eq!(native["id"], event.id.to_string());
+
+    let native: Value = serde_json::from_str(
+        &event
+            .to_payload(PayloadFormat::Native, NameFormat::S3)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(native["eventName"], "s3:ObjectCreated:Put");
+    // only the payload changes, the event itself keeps the constant form
+    assert_eq!(
+        serde_json::to_value(&event).unwrap()["eventName"],
+        "OBJECT_CREATED_PUT"
+    );
+
+    let batch: Value = serde_json::from_str(
+        &Event::to_batch_payload(&[event], PayloadFormat::Native, NameFormat::S3).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(batch[0]["eventName"], "s3:ObjectCreated:Put");
+}
+
+#[test]
+fn test_name_s3_forms() {
+    assert_eq!(Name::ObjectCreatedPut.as_s3_str(), "s3:ObjectCreated:Put");
+    assert_eq!(
+        Name::from_s3_str("s3:ObjectCreated:*").unwrap(),
+        Name::ObjectCreatedAll
+    );
+    assert_eq!(Name::from_s3_str("s3:*").unwrap(), Name::Everything);
+    assert!(matches!(
+        Name::from_s3_str("OBJECT_CREATED_PUT"),
+        Err(Error::UnknownEventName(_))
+    ));
+
+    let names: Vec<Name> =
+        serde_json::from_str(r#"["OBJECT_REMOVED_DELETE", "s3:ObjectRemoved:*"]"#).unwrap();
+    assert_eq!(
+        names,
+        vec![Name::ObjectRemovedDelete, Name::ObjectRemovedAll]
+    );
+    assert!(serde_json::from_str::<Name>(r#""s3:ObjectRemoved:Shred""#).is_err());
+}
+
+#[test]
+fn test_event_occurred_at() {
+    let mut event = test_event_in(Name::ObjectCreatedPut, "photos", "a.jpg");
+    event.event_time = "2023-10-01T12:00:00.000+02:00".to_string();
+    assert_eq!(
+        event.occurred_at().to_rfc3339(),
+        "2023-10-01T10:00:00+00:00"
+    );
+
+    event.event_time = "yesterday".to_string();
+    assert_eq!(event.occurred_at(), event.timestamp);
+}
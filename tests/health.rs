@@ -0,0 +1,121 @@
+mod common;
+
+use common::{RecordingAdapter, temp_store_path, test_event, wait_until};
+use event_notification::{
+    ChannelAdapter, DeadLetterStore, Error, EventStorage, HealthCheck, MemoryStore, RulesMap,
+    WebhookAdapter, WebhookConfig,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_health_check_gates_delivery() {
+    let adapter = RecordingAdapter::new("webhook");
+    let health = HealthCheck::new(adapter.clone());
+    assert!(health.active());
+    assert!(health.last_checked().is_none());
+
+    adapter.set_failing(true);
+    assert!(!health.check().await);
+    assert!(health.last_checked().is_some());
+
+    // rejected without reaching the adapter until a check passes again
+    adapter.set_failing(false);
+    assert!(matches!(
+        health.send(&test_event("a")).await,
+        Err(Error::TargetInactive(_))
+    ));
+    assert!(adapter.keys().is_empty());
+
+    assert!(health.check().await);
+    health.send(&test_event("b")).await.unwrap();
+    assert_eq!(adapter.keys(), vec!["b"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_inactive_target_without_queue_holds_events() {
+    let adapter = RecordingAdapter::new("webhook");
+    let health = Arc::new(HealthCheck::new(adapter.clone()));
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![health.clone()];
+    let dead_letters = Arc::new(DeadLetterStore::new(&temp_store_path()).await.unwrap());
+    let store = Arc::new(MemoryStore::new());
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        store.clone(),
+        HashMap::new(),
+        Arc::new(RulesMap::default()),
+        Some(dead_letters.clone()),
+        shutdown.clone(),
+    ));
+
+    adapter.set_failing(true);
+    assert!(!health.check().await);
+    adapter.set_failing(false);
+    for key in ["a", "b"] {
+        let mut event = test_event(key);
+        event.channels = vec!["webhook".to_string()].into();
+        tx.send(event).await.unwrap();
+    }
+
+    // neither dead-lettered nor acknowledged while the target is inactive
+    wait_until(async || store.load_pending().await.unwrap().len() == 2).await;
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert!(dead_letters.list().await.unwrap().is_empty());
+    assert_eq!(store.load_pending().await.unwrap().len(), 2);
+    assert!(adapter.keys().is_empty());
+
+    assert!(health.check().await);
+    wait_until(async || adapter.keys().len() == 2).await;
+    wait_until(async || store.load_pending().await.unwrap().is_empty()).await;
+    assert!(dead_letters.list().await.unwrap().is_empty());
+
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+}
+
+/// Serves every request with an empty response of `status`, returns the endpoint.
+async fn serve_status(status: &'static str) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).await;
+            let response = format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\n\r\n");
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    format!("http://{addr}/webhook")
+}
+
+#[tokio::test]
+async fn test_webhook_health_check() {
+    let webhook = |endpoint: String| {
+        WebhookAdapter::new(WebhookConfig {
+            endpoint,
+            auth_token: None,
+            custom_headers: None,
+            max_retries: 1,
+            timeout: 1000,
+            target: Default::default(),
+        })
+    };
+
+    // servers without HEAD support are reachable
+    for status in ["200 OK", "405 Method Not Allowed", "501 Not Implemented"] {
+        let adapter = webhook(serve_status(status).await);
+        assert!(adapter.is_active().await.unwrap(), "{status}");
+    }
+    let adapter = webhook(serve_status("503 Service Unavailable").await);
+    assert!(!adapter.is_active().await.unwrap());
+    let adapter = webhook("http://127.0.0.1:1/webhook".to_string());
+    assert!(!adapter.is_active().await.unwrap());
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "http-producer")]
+
+mod common;
+
+use common::{temp_store_path, test_event, test_event_in};
+use event_notification::{EventSender, HttpProducerConfig, Name};
+use event_notification::{NotificationConfig, NotificationSystem};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_stream::StreamExt;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Starts a system serving HTTP on a random port, returns its sender and the port.
+async fn start_system(http: HttpProducerConfig) -> (EventSender, u16) {
+    let port = 20000 + fastrand::u16(..20000);
+    let mut system = NotificationSystem::new(NotificationConfig {
+        store_path: temp_store_path(),
+        http: HttpProducerConfig { port, ..http },
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let sender = system.sender();
+    tokio::spawn(async move { system.start(Vec::new()).await });
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    (sender, port)
+}
+
+/// Sends a `GET` request for `path` and returns the open connection.
+async fn get(port: u16, path: &str, headers: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{headers}\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    stream
+}
+
+/// Reads from `stream` until the response holds `needle`, returns what was read.
+async fn read_until(stream: &mut TcpStream, needle: &str) -> String {
+    let mut response = String::new();
+    let mut buf = [0; 4096];
+    while !response.contains(needle) {
+        let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf))
+            .await
+            .unwrap_or_else(|_| panic!("{needle} not received, got {response}"))
+            .unwrap();
+        assert!(read > 0, "connection closed, got {response}");
+        response.push_str(&String::from_utf8_lossy(&buf[..read]));
+    }
+    response
+}
+
+#[tokio::test]
+async fn test_listen_streams_matching_events() {
+    let (sender, port) = start_system(HttpProducerConfig::default()).await;
+    let mut stream = get(port, "/listen?bucket=photos&events=s3:ObjectCreated:*", "").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let events = [
+        (Name::ObjectRemovedDelete, "photos", "removed.jpg"),
+        (Name::ObjectCreatedPut, "docs", "other-bucket.jpg"),
+        (Name::ObjectCreatedPut, "photos", "created.jpg"),
+    ];
+    for (name, bucket, key) in events {
+        sender.send(test_event_in(name, bucket, key)).await.unwrap();
+    }
+
+    let response = read_until(&mut stream, "created.jpg").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("application/x-ndjson"));
+    assert!(response.contains(r#""Records":[{"#));
+    assert!(!response.contains("removed.jpg"));
+    assert!(!response.contains("other-bucket.jpg"));
+}
+
+#[tokio::test]
+async fn test_sse_requires_token_and_limits_connections() {
+    let (sender, port) = start_system(HttpProducerConfig {
+        auth_token: Some("secret".to_string()),
+        max_connections: 1,
+        ..Default::default()
+    })
+    .await;
+
+    let mut denied = get(port, "/listen/sse", "").await;
+    assert!(
+        read_until(&mut denied, "\r\n")
+            .await
+            .starts_with("HTTP/1.1 401")
+    );
+
+    let mut stream = get(port, "/listen/sse", "Authorization: Bearer secret\r\n").await;
+    read_until(&mut stream, "text/event-stream").await;
+    let mut limited = get(port, "/listen/sse?access_token=secret", "").await;
+    assert!(
+        read_until(&mut limited, "\r\n")
+            .await
+            .starts_with("HTTP/1.1 503")
+    );
+
+    let event = test_event("a.txt");
+    let id = event.id.to_string();
+    sender.send(event).await.unwrap();
+    let response = read_until(&mut stream, "a.txt").await;
+    assert!(response.contains("event: s3:ObjectCreated:Put"));
+    assert!(response.contains(&format!("id: {id}")));
+
+    // closing the stream frees the connection
+    drop(stream);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut reopened = get(port, "/listen/sse?access_token=secret", "").await;
+    assert!(
+        read_until(&mut reopened, "\r\n")
+            .await
+            .starts_with("HTTP/1.1 200")
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_streams_matching_events() {
+    let (sender, port) = start_system(HttpProducerConfig::default()).await;
+    let url = format!("ws://127.0.0.1:{port}/listen/ws?suffix=.jpg");
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for key in ["a.txt", "b.jpg"] {
+        sender.send(test_event(key)).await.unwrap();
+    }
+    let message = tokio::time::timeout(Duration::from_secs(2), socket.next())
+        .await
+        .expect("no message received")
+        .unwrap()
+        .unwrap();
+    let Message::Text(text) = message else {
+        panic!("unexpected message {message:?}");
+    };
+    assert!(text.contains("b.jpg"));
+    assert!(!text.contains("a.txt"));
+}
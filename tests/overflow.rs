@@ -0,0 +1,103 @@
+mod common;
+
+use common::{RecordingAdapter, temp_store_path, test_event, wait_until};
+use event_notification::{
+    ChannelAdapter, Error, Event, NotificationConfig, NotificationSystem, OverflowConfig,
+    OverflowPolicy,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Creates an event for `key` routed to the adapter `channel`.
+fn event_for(key: &str, channel: &str) -> Event {
+    let event = test_event(key);
+    Event::create(
+        event.event_name,
+        event.s3,
+        event.source,
+        vec![channel.to_string()],
+    )
+}
+
+async fn system(channel_capacity: usize, overflow: OverflowConfig) -> NotificationSystem {
+    NotificationSystem::new(NotificationConfig {
+        store_path: temp_store_path(),
+        channel_capacity,
+        overflow,
+        ..Default::default()
+    })
+    .await
+    .unwrap()
+}
+
+/// Sends events one at a time, letting the channel hand them over to the event bus in between.
+/// With the clock paused the sleep only returns once every other task is idle.
+async fn send_all(system: &NotificationSystem, keys: &[&str]) -> Result<(), Error> {
+    for key in keys {
+        system.send_event(event_for(key, "recording")).await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_overflow_policies() {
+    // the event bus is not running: two events are in flight towards it and two fill the channel
+    let drop_oldest = system(
+        2,
+        OverflowConfig {
+            policy: OverflowPolicy::DropOldest,
+            ..Default::default()
+        },
+    )
+    .await;
+    send_all(&drop_oldest, &["a", "b", "c", "d", "e", "f"])
+        .await
+        .unwrap();
+    let sender = drop_oldest.sender();
+    assert_eq!(sender.len(), 2);
+    assert_eq!(sender.dropped(), 2);
+    assert!(matches!(
+        drop_oldest.try_send_event(test_event("g")),
+        Err(Error::ChannelFull(_))
+    ));
+
+    let timeout = system(
+        1,
+        OverflowConfig {
+            policy: OverflowPolicy::Timeout,
+            timeout_ms: 50,
+            ..Default::default()
+        },
+    )
+    .await;
+    send_all(&timeout, &["a", "b", "c"]).await.unwrap();
+    let result = send_all(&timeout, &["d"]).await;
+    assert!(matches!(result, Err(Error::ChannelFull(event)) if event.s3.object.key == "d"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_spilled_events_are_delivered_in_order() {
+    let store_path = temp_store_path();
+    let spill_dir = format!("{store_path}/spill");
+    let mut system = NotificationSystem::new(NotificationConfig {
+        store_path,
+        channel_capacity: 1,
+        overflow: OverflowConfig {
+            policy: OverflowPolicy::Spill,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    send_all(&system, &["a", "b", "c", "d", "e"]).await.unwrap();
+    assert_eq!(std::fs::read_dir(&spill_dir).unwrap().count(), 2);
+
+    let adapter = RecordingAdapter::new("recording");
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![adapter.clone()];
+    tokio::spawn(async move { system.start(adapters).await });
+    wait_until(async || adapter.keys().len() == 5).await;
+    assert_eq!(adapter.keys(), vec!["a", "b", "c", "d", "e"]);
+    assert_eq!(std::fs::read_dir(&spill_dir).unwrap().count(), 0);
+}
@@ -0,0 +1,100 @@
+use event_notification::{Error, RetryOn, RetryPolicy};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay_ms: 1,
+        max_delay_ms: 4,
+        jitter: false,
+        ..Default::default()
+    }
+}
+
+/// Runs `policy` around an operation failing with `error` `failures` times, returns the result and
+/// the number of attempts.
+async fn run(
+    policy: &RetryPolicy,
+    failures: u32,
+    error: fn() -> Error,
+) -> (Result<(), Error>, u32) {
+    let attempts = AtomicU32::new(0);
+    let result = policy
+        .run(|| async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < failures {
+                Err(error())
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+    (result, attempts.load(Ordering::SeqCst))
+}
+
+fn transient() -> Error {
+    Error::Io(Box::new(std::io::Error::other("connection reset")))
+}
+
+fn permanent() -> Error {
+    Error::ConfigError("bad endpoint".to_string())
+}
+
+#[tokio::test]
+async fn test_retry_policy() {
+    let (result, attempts) = run(&policy(3), 2, transient).await;
+    assert!(result.is_ok());
+    assert_eq!(attempts, 3);
+
+    let (result, attempts) = run(&policy(3), 5, transient).await;
+    assert!(matches!(result, Err(Error::Io(_))));
+    assert_eq!(attempts, 3);
+
+    let (result, attempts) = run(&policy(3), 5, permanent).await;
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+    assert_eq!(attempts, 1);
+
+    let all = RetryPolicy {
+        retry_on: RetryOn::All,
+        ..policy(3)
+    };
+    let (result, attempts) = run(&all, 1, permanent).await;
+    assert!(result.is_ok());
+    assert_eq!(attempts, 2);
+
+    let deadline = RetryPolicy {
+        base_delay_ms: 50,
+        max_delay_ms: 50,
+        deadline_ms: Some(10),
+        ..policy(3)
+    };
+    let (result, attempts) = run(&deadline, 5, transient).await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn test_retry_delays() {
+    let policy = policy(10);
+    let delays: Vec<Duration> = (1..=4).map(|retry| policy.delay(retry)).collect();
+    assert_eq!(delays, [1, 2, 4, 4].map(Duration::from_millis));
+
+    let jittered = RetryPolicy {
+        base_delay_ms: 100,
+        max_delay_ms: 100,
+        jitter: true,
+        ..policy
+    };
+    let delay = jittered.delay(3);
+    assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+
+    assert!(RetryPolicy::never().validate().is_ok());
+    assert!(
+        RetryPolicy {
+            max_attempts: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err()
+    );
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{RecordingAdapter, test_event_in};
+use event_notification::{ChannelAdapter, MemoryStore, Name, NameSet, NotificationRule, RulesMap};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+fn rule(
+    events: Vec<Name>,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+    target: &str,
+) -> NotificationRule {
+    NotificationRule {
+        id: None,
+        bucket: Some("photos".to_string()),
+        events,
+        prefix: prefix.map(str::to_string),
+        suffix: suffix.map(str::to_string),
+        targets: vec![target.to_string()],
+    }
+}
+
+#[test]
+fn test_name_set() {
+    assert_eq!(Name::ObjectAccessedGet.mask(), 1);
+    assert_eq!(Name::from_repr(1), Some(Name::ObjectAccessedGet));
+    assert_eq!(Name::from_repr(0), None);
+    assert_eq!(NameSet::all().len(), 32);
+    assert_eq!(NameSet::from(Name::Everything), NameSet::all());
+    assert!(NameSet::all().contains(Name::ObjectAccessedGet));
+
+    let created = NameSet::from(Name::ObjectCreatedAll);
+    assert_eq!(created.len(), 8);
+    assert!(created.contains(Name::ObjectCreatedPut));
+    assert!(!created.contains(Name::ObjectRemovedDelete));
+
+    let set: NameSet = [Name::ObjectCreatedPut, Name::ObjectRemovedDelete]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        vec![Name::ObjectCreatedPut, Name::ObjectRemovedDelete]
+    );
+    assert_eq!(set & created, NameSet::from(Name::ObjectCreatedPut));
+    assert_eq!((set | created).len(), 9);
+
+    let json = serde_json::to_string(&set).unwrap();
+    assert_eq!(json, r#"["OBJECT_CREATED_PUT","OBJECT_REMOVED_DELETE"]"#);
+    let parsed: NameSet = serde_json::from_str(r#"["s3:ObjectRemoved:*"]"#).unwrap();
+    assert_eq!(parsed, NameSet::from(Name::ObjectRemovedAll));
+
+    let event = test_event_in(Name::ObjectAccessedGet, "photos", "a.txt");
+    assert!(event.is_type(Name::ObjectAccessedAll));
+    assert!(event.is_type(Name::Everything));
+    assert!(!event.is_type(Name::ObjectCreatedAll));
+}
+
+#[test]
+fn test_rules_match_names_and_key_filters() {
+    let rules = RulesMap::new(vec![
+        rule(
+            vec![Name::ObjectCreatedAll],
+            Some("images/"),
+            Some(".jpg"),
+            "thumbnails",
+        ),
+        rule(vec![Name::ObjectRemovedDelete], None, None, "audit"),
+        rule(vec![Name::Everything], Some("images/"), None, "audit"),
+    ]);
+
+    let created = test_event_in(Name::ObjectCreatedCopy, "photos", "images/cat.jpg");
+    assert_eq!(rules.match_targets(&created), vec!["thumbnails", "audit"]);
+
+    let wrong_suffix = test_event_in(Name::ObjectCreatedPut, "photos", "images/cat.png");
+    assert_eq!(rules.match_targets(&wrong_suffix), vec!["audit"]);
+
+    let removed = test_event_in(Name::ObjectRemovedDelete, "photos", "docs/a.txt");
+    assert_eq!(rules.match_targets(&removed), vec!["audit"]);
+
+    let other_bucket = test_event_in(Name::ObjectRemovedDelete, "videos", "images/cat.jpg");
+    assert!(rules.match_targets(&other_bucket).is_empty());
+}
+
+#[tokio::test]
+async fn test_event_bus_routes_by_rules() {
+    let thumbnails = RecordingAdapter::new("thumbnails");
+    let audit = RecordingAdapter::new("audit");
+    let adapters: Vec<Arc<dyn ChannelAdapter>> = vec![thumbnails.clone(), audit.clone()];
+    let rules = RulesMap::new(vec![
+        rule(
+            vec![Name::ObjectCreatedAll],
+            Some("images/"),
+            None,
+            "thumbnails",
+        ),
+        rule(vec![Name::ObjectRemovedAll], None, None, "audit"),
+    ]);
+
+    let (tx, rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let bus = tokio::spawn(event_notification::event_bus(
+        rx,
+        adapters,
+        Arc::new(MemoryStore::new()),
+        HashMap::new(),
+        Arc::new(rules),
+        None,
+        shutdown.clone(),
+    ));
+
+    for event in [
+        test_event_in(Name::ObjectCreatedPut, "photos", "images/a.jpg"),
+        test_event_in(Name::ObjectCreatedPut, "photos", "docs/b.txt"),
+        test_event_in(Name::ObjectRemovedDelete, "photos", "images/a.jpg"),
+    ] {
+        tx.send(event).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.cancel();
+    bus.await.unwrap().unwrap();
+
+    assert_eq!(thumbnails.keys(), vec!["images/a.jpg"]);
+    assert_eq!(audit.keys(), vec!["images/a.jpg"]);
+}
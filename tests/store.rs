@@ -0,0 +1,197 @@
+mod common;
+
+use common::{temp_store_path, test_event};
+use event_notification::{
+    Error, EventStorage, FileStore, FsyncPolicy, QueueStore, StoreBackend, StoreConfig,
+};
+use event_notification::{Event, Log, create_storage};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_restart_replays_only_undelivered_events() {
+    let path = temp_store_path();
+    let targets = vec!["webhook".to_string(), "kafka".to_string()];
+    let delivered = test_event("delivered.txt");
+    let partial = test_event("partial.txt");
+
+    {
+        let store = FileStore::new(&path).await.unwrap();
+        store.append(&delivered, &targets).await.unwrap();
+        store.append(&partial, &targets).await.unwrap();
+        store.ack(delivered.id, "webhook").await.unwrap();
+        store.ack(delivered.id, "kafka").await.unwrap();
+        store.ack(partial.id, "kafka").await.unwrap();
+    }
+
+    let store = FileStore::new(&path).await.unwrap();
+    let pending = store.load_pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event.id, partial.id);
+    assert_eq!(pending[0].targets, vec!["webhook".to_string()]);
+
+    // replaying an event does not record it twice and only returns the missing targets
+    let remaining = store.append(&partial, &targets).await.unwrap();
+    assert_eq!(remaining, vec!["webhook".to_string()]);
+
+    store.ack(partial.id, "webhook").await.unwrap();
+    drop(store);
+    let store = FileStore::new(&path).await.unwrap();
+    assert!(store.load_pending().await.unwrap().is_empty());
+
+    let _ = tokio::fs::remove_dir_all(&path).await;
+}
+
+/// Checks the ledger semantics every storage backend has to provide.
+async fn assert_ledger_semantics(store: &dyn EventStorage) {
+    let targets = vec!["webhook".to_string(), "kafka".to_string()];
+    let first = test_event("first.txt");
+    let second = test_event("second.txt");
+
+    store.append(&first, &targets).await.unwrap();
+    store.append(&second, &targets).await.unwrap();
+    store.ack(first.id, "webhook").await.unwrap();
+    store.ack(first.id, "kafka").await.unwrap();
+    store.ack(second.id, "kafka").await.unwrap();
+
+    let pending = store.load_pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event.id, second.id);
+    assert_eq!(pending[0].targets, vec!["webhook".to_string()]);
+    assert_eq!(
+        store.append(&second, &targets).await.unwrap(),
+        vec!["webhook".to_string()]
+    );
+
+    let stats = store.stats().await.unwrap();
+    assert_eq!(stats.pending_events, 1);
+    assert_eq!(stats.pending_deliveries, 1);
+
+    assert_eq!(store.purge().await.unwrap(), 1);
+    assert!(store.load_pending().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_storage_backends() {
+    for backend in [StoreBackend::Memory, StoreBackend::File] {
+        let path = temp_store_path();
+        let config = StoreConfig {
+            backend,
+            ..Default::default()
+        };
+        let store = create_storage(&path, config).await.unwrap();
+        assert_ledger_semantics(store.as_ref()).await;
+        let _ = tokio::fs::remove_dir_all(&path).await;
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_storage_survives_restart() {
+    let path = temp_store_path();
+    let config = StoreConfig {
+        backend: StoreBackend::Sqlite,
+        ..Default::default()
+    };
+    let event = test_event("sqlite.txt");
+    {
+        let store = create_storage(&path, config.clone()).await.unwrap();
+        assert_ledger_semantics(store.as_ref()).await;
+        store
+            .append(&event, &["webhook".to_string()])
+            .await
+            .unwrap();
+    }
+
+    let store = create_storage(&path, config).await.unwrap();
+    let pending = store.load_pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event.id, event.id);
+
+    let _ = tokio::fs::remove_dir_all(&path).await;
+}
+
+#[tokio::test]
+async fn test_queue_store_keeps_entries_in_order_up_to_limit() {
+    let path = temp_store_path();
+    let queue = QueueStore::new(&path, 2).await.unwrap();
+    let first = test_event("first.txt");
+    let second = test_event("second.txt");
+    let log = |event: &Event| Log {
+        event_name: event.event_name,
+        key: event.s3.object.key.clone(),
+        records: vec![event.clone()],
+    };
+
+    queue.put(&log(&first)).await.unwrap();
+    queue.put(&log(&second)).await.unwrap();
+    assert!(matches!(
+        queue.put(&log(&first)).await,
+        Err(Error::QueueFull(_))
+    ));
+
+    // entries survive reopening the queue and are listed oldest first
+    let queue = QueueStore::new(&path, 2).await.unwrap();
+    assert_eq!(queue.len(), 2);
+    let keys = queue.list().await.unwrap();
+    assert_eq!(queue.get(&keys[0]).await.unwrap().records[0].id, first.id);
+    assert_eq!(queue.get(&keys[1]).await.unwrap().records[0].id, second.id);
+
+    queue.del(&keys[0]).await.unwrap();
+    assert_eq!(queue.len(), 1);
+    queue.put(&log(&first)).await.unwrap();
+
+    // concurrent puts never overrun the limit
+    let queue = Arc::new(QueueStore::new(&temp_store_path(), 4).await.unwrap());
+    let puts: Vec<_> = (0..16)
+        .map(|_| {
+            let queue = queue.clone();
+            let log = log(&first);
+            tokio::spawn(async move { queue.put(&log).await })
+        })
+        .collect();
+    let mut stored = 0;
+    for put in puts {
+        match put.await.unwrap() {
+            Ok(_) => stored += 1,
+            Err(e) => assert!(matches!(e, Error::QueueFull(_))),
+        }
+    }
+    assert_eq!(stored, 4);
+    assert_eq!(queue.len(), 4);
+    assert_eq!(queue.list().await.unwrap().len(), 4);
+
+    let _ = tokio::fs::remove_dir_all(&path).await;
+}
+
+#[tokio::test]
+async fn test_recovery_truncates_torn_tail() {
+    let path = temp_store_path();
+    let config = StoreConfig {
+        fsync: FsyncPolicy::Always,
+        ..Default::default()
+    };
+    let targets = vec!["webhook".to_string()];
+    let first = test_event("first.txt");
+    let second = test_event("second.txt");
+
+    {
+        let store = FileStore::with_config(&path, config.clone()).await.unwrap();
+        store.append(&first, &targets).await.unwrap();
+        store.append(&second, &targets).await.unwrap();
+    }
+
+    // simulate a crash in the middle of writing a record
+    let mut entries = std::fs::read_dir(&path).unwrap();
+    let segment = entries.next().unwrap().unwrap().path();
+    let mut data = std::fs::read(&segment).unwrap();
+    data.extend_from_slice(&[64, 0, 0, 0, 1, 2, 3, 4, b'{']);
+    std::fs::write(&segment, data).unwrap();
+
+    let store = FileStore::with_config(&path, config).await.unwrap();
+    let pending = store.load_pending().await.unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].event.id, first.id);
+    assert_eq!(pending[1].event.id, second.id);
+
+    let _ = tokio::fs::remove_dir_all(&path).await;
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{temp_store_path, test_event, test_event_in};
+use event_notification::{EventSender, LagPolicy, Name, NotificationConfig, NotificationSystem};
+use event_notification::{Subscription, SubscriptionFilter};
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+/// Starts a system without adapters, returns its sender and the subscriptions to `filters`.
+async fn start_system(
+    subscriber_capacity: usize,
+    filters: Vec<SubscriptionFilter>,
+) -> (EventSender, Vec<Subscription>) {
+    let mut system = NotificationSystem::new(NotificationConfig {
+        store_path: temp_store_path(),
+        subscriber_capacity,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let subscriptions = filters.into_iter().map(|f| system.subscribe(f)).collect();
+    let sender = system.sender();
+    tokio::spawn(async move { system.start(Vec::new()).await });
+    (sender, subscriptions)
+}
+
+async fn next_key(subscription: &mut Subscription) -> Option<String> {
+    tokio::time::timeout(Duration::from_millis(500), subscription.next())
+        .await
+        .expect("no event received")
+        .map(|event| event.s3.object.key)
+}
+
+#[tokio::test]
+async fn test_filtered_subscriptions() {
+    let (sender, mut subscriptions) = start_system(
+        16,
+        vec![
+            SubscriptionFilter::new(),
+            SubscriptionFilter::new()
+                .events([Name::ObjectRemovedAll])
+                .bucket("photos")
+                .prefix("2024/"),
+        ],
+    )
+    .await;
+    sender.send(test_event("a.txt")).await.unwrap();
+    let events = [
+        (Name::ObjectRemovedDelete, "photos", "2023/b.jpg"),
+        (Name::ObjectCreatedPut, "photos", "2024/c.jpg"),
+        (Name::ObjectRemovedDelete, "docs", "2024/d.jpg"),
+        (Name::ObjectRemovedDelete, "photos", "2024/e.jpg"),
+    ];
+    for (name, bucket, key) in events {
+        sender.send(test_event_in(name, bucket, key)).await.unwrap();
+    }
+
+    let all = &mut subscriptions[0];
+    for key in [
+        "a.txt",
+        "2023/b.jpg",
+        "2024/c.jpg",
+        "2024/d.jpg",
+        "2024/e.jpg",
+    ] {
+        assert_eq!(next_key(all).await.as_deref(), Some(key));
+    }
+    let filtered = &mut subscriptions[1];
+    assert_eq!(next_key(filtered).await.as_deref(), Some("2024/e.jpg"));
+}
+
+#[tokio::test]
+async fn test_lagging_subscriptions() {
+    let (sender, mut subscriptions) = start_system(
+        2,
+        vec![
+            SubscriptionFilter::new(),
+            SubscriptionFilter::new().lag(LagPolicy::Close),
+        ],
+    )
+    .await;
+    for key in ["a", "b", "c", "d", "e"] {
+        sender.send(test_event(key)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let close = &mut subscriptions[1];
+    assert_eq!(next_key(close).await, None);
+    assert_eq!(close.missed(), 3);
+
+    // skipping carries on with the events still available
+    let skip = &mut subscriptions[0];
+    assert_eq!(next_key(skip).await.as_deref(), Some("d"));
+    assert_eq!(skip.missed(), 3);
+    assert_eq!(next_key(skip).await.as_deref(), Some("e"));
+    sender.send(test_event("f")).await.unwrap();
+    assert_eq!(next_key(skip).await.as_deref(), Some("f"));
+}
+
+#[test]
+fn test_filter_from_query() {
+    let filter = SubscriptionFilter::from_query(
+        "bucket=photos&prefix=2024%2F&events=s3:ObjectCreated:*,OBJECT_REMOVED_DELETE&events=s3:ObjectAccessed:Get&other=1",
+    )
+    .unwrap();
+    assert_eq!(filter.bucket.as_deref(), Some("photos"));
+    assert_eq!(filter.prefix.as_deref(), Some("2024/"));
+    assert_eq!(filter.suffix, None);
+    assert!(filter.events.contains(Name::ObjectCreatedCopy));
+    assert!(filter.events.contains(Name::ObjectRemovedDelete));
+    assert!(filter.events.contains(Name::ObjectAccessedGet));
+    assert!(
+        !filter
+            .events
+            .contains(Name::ObjectRemovedDeleteMarkerCreated)
+    );
+
+    assert_eq!(
+        SubscriptionFilter::from_query("").unwrap(),
+        SubscriptionFilter::new()
+    );
+    assert!(SubscriptionFilter::from_query("events=s3:Nope").is_err());
+}
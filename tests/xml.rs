@@ -0,0 +1,107 @@
+#![cfg(feature = "webhook")]
+
+use event_notification::{
+    AdapterConfig, Error, Name, NotificationConfiguration, TargetConfig, WebhookConfig,
+};
+
+const DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<NotificationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <QueueConfiguration>
+    <Id>thumbnails</Id>
+    <Filter>
+      <S3Key>
+        <FilterRule><Name>prefix</Name><Value>images/</Value></FilterRule>
+        <FilterRule><Name>Suffix</Name><Value>.jpg</Value></FilterRule>
+      </S3Key>
+    </Filter>
+    <Queue>arn:rustfs:sqs:us-east-1:primary:webhook</Queue>
+    <Event>s3:ObjectCreated:*</Event>
+    <Event>s3:ObjectRemoved:Delete</Event>
+  </QueueConfiguration>
+  <TopicConfiguration>
+    <Topic>arn:rustfs:sqs:us-east-1:primary:webhook</Topic>
+    <Event>s3:ObjectAccessed:Get</Event>
+  </TopicConfiguration>
+</NotificationConfiguration>"#;
+
+fn webhook(id: &str) -> AdapterConfig {
+    AdapterConfig::Webhook(WebhookConfig {
+        endpoint: "http://localhost:8080/webhook".to_string(),
+        auth_token: None,
+        custom_headers: None,
+        max_retries: 1,
+        timeout: 5,
+        target: TargetConfig {
+            id: Some(id.to_string()),
+            ..Default::default()
+        },
+    })
+}
+
+#[test]
+fn test_notification_configuration_round_trip() {
+    let config = NotificationConfiguration::from_xml(DOCUMENT).unwrap();
+    assert_eq!(config.queues.len(), 1);
+    assert_eq!(config.topics.len(), 1);
+    let queue = &config.queues[0];
+    assert_eq!(queue.id.as_deref(), Some("thumbnails"));
+    assert_eq!(queue.arn.id, "primary");
+    assert_eq!(
+        queue.events,
+        vec![Name::ObjectCreatedAll, Name::ObjectRemovedDelete]
+    );
+    assert_eq!(queue.prefix.as_deref(), Some("images/"));
+    assert_eq!(queue.suffix.as_deref(), Some(".jpg"));
+
+    let xml = config.to_xml().unwrap();
+    assert!(xml.contains("<Event>s3:ObjectCreated:*</Event>"));
+    assert_eq!(NotificationConfiguration::from_xml(&xml).unwrap(), config);
+
+    let adapters = vec![webhook("primary")];
+    let rules = config.to_rules("photos", &adapters).unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].bucket.as_deref(), Some("photos"));
+    assert_eq!(rules[0].targets, vec!["primary"]);
+
+    let served =
+        NotificationConfiguration::from_rules(&rules, "photos", &adapters, "us-east-1").unwrap();
+    assert_eq!(served.queues[0], config.queues[0]);
+    assert!(
+        NotificationConfiguration::from_rules(&rules, "videos", &adapters, "us-east-1")
+            .unwrap()
+            .queues
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_notification_configuration_errors() {
+    let unknown_event = DOCUMENT.replace("s3:ObjectRemoved:Delete", "s3:ObjectRemoved:Shred");
+    assert!(matches!(
+        NotificationConfiguration::from_xml(&unknown_event),
+        Err(Error::UnknownEventName(name)) if name == "s3:ObjectRemoved:Shred"
+    ));
+
+    let bad_rule = DOCUMENT.replace("<Name>prefix</Name>", "<Name>infix</Name>");
+    assert!(matches!(
+        NotificationConfiguration::from_xml(&bad_rule),
+        Err(Error::InvalidFilterRule(_))
+    ));
+
+    let bad_arn = DOCUMENT.replace("arn:rustfs:sqs:us-east-1:primary:webhook", "primary");
+    assert!(matches!(
+        NotificationConfiguration::from_xml(&bad_arn),
+        Err(Error::UnknownTarget(_))
+    ));
+
+    assert!(matches!(
+        NotificationConfiguration::from_xml("<NotificationConfiguration><Queue"),
+        Err(Error::Xml(_))
+    ));
+
+    let config = NotificationConfiguration::from_xml(DOCUMENT).unwrap();
+    assert!(matches!(
+        config.to_rules("photos", &[webhook("secondary")]),
+        Err(Error::UnknownTarget(arn)) if arn == "arn:rustfs:sqs:us-east-1:primary:webhook"
+    ));
+}